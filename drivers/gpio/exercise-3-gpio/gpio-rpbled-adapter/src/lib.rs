@@ -2,17 +2,38 @@
 
 #![no_std]
 
-use core::ops::Deref;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
 use kernel::{
-    file::{self, File},
+    bindings, container_of,
+    file::{self, File, IoctlCommand, IoctlHandler},
+    hrtimer::{HrTimer, HrTimerCallback, HrTimerRestart},
     io_buffer::{IoBufferReader, IoBufferWriter},
-    miscdev, new_mutex,
+    ioctl::{_IO, _IOR, _IOW},
+    miscdev, new_condvar, new_mutex,
     prelude::*,
-    sync::{Arc, ArcBorrow, Mutex},
+    str::CString,
+    sync::{Arc, ArcBorrow, CondVar, Mutex},
     error::code,
 };
 use led_pure_driver::Led;
 
+const LED_IOC_MAGIC: u32 = b'L' as u32;
+
+/// Reads back the current LED brightness as a `u8` (0 = off, 255 = full-on).
+const LED_GET_STATE: u32 = _IOR::<u8>(LED_IOC_MAGIC, 1);
+/// Sets the LED brightness from a `u8` (0 = off, 255 = full-on).
+const LED_SET_STATE: u32 = _IOW::<u8>(LED_IOC_MAGIC, 2);
+/// Flips the LED fully on/off, ignoring any graded brightness previously set.
+const LED_TOGGLE: u32 = _IO(LED_IOC_MAGIC, 3);
+/// Starts a hardware-timer blink with the given half-period in milliseconds, or stops blinking
+/// and goes solid if the period is 0.
+const LED_SET_BLINK: u32 = _IOW::<u32>(LED_IOC_MAGIC, 4);
+
+/// GPIO line number backing each `rust_led<index>` device node, in order.
+const LED_GPIOS: &[u32] = &[17, 27];
+
 module! {
     type: RustLed,
     name: "rust_led_adapter",
@@ -21,80 +42,281 @@ module! {
     license: "GPL",
 }
 
+/// The LED's live, software-visible state. `generation` is bumped on every [`file::Operations::write`]
+/// so a blocked [`file::Operations::read`] can tell its own last-seen generation apart from a
+/// fresher one without missing a change that happened between calls.
+struct LedStateInner {
+    led: Led,
+    /// Steady-state brightness (0 = off, 255 = full-on); what the LED shows while not blinking,
+    /// and what it alternates with 0 while blinking.
+    brightness: u8,
+    /// Which phase of a blink is currently being shown; meaningless while `blink_period_ms == 0`.
+    blink_on: bool,
+    generation: u64,
+    /// Half-period of an in-progress hardware-timer blink, in milliseconds; `0` means solid.
+    blink_period_ms: u32,
+}
+
 #[pin_data]
 struct LedData {
     #[pin]
-    led: Mutex<Led>,
+    inner: Mutex<LedStateInner>,
+    #[pin]
+    state_changed: CondVar,
+    timer: HrTimer,
 }
 
 impl LedData {
-    fn try_new() -> Result<Arc<Self>> {
-        pr_info!("Led device created\n");
+    fn try_new(gpio: u32) -> Result<Arc<Self>> {
+        pr_info!("Led device created on gpio{}\n", gpio);
         Ok(Arc::pin_init(pin_init!(Self {
-            led <- new_mutex!(Led::new())
+            inner <- new_mutex!(LedStateInner {
+                led: Led::new(gpio),
+                brightness: 0,
+                blink_on: false,
+                generation: 0,
+                blink_period_ms: 0,
+            }),
+            state_changed <- new_condvar!(),
+            timer: HrTimer::new(),
         }))?)
     }
 }
 
+impl HrTimerCallback for LedData {
+    unsafe fn from_timer(timer: *mut bindings::hrtimer) -> *const Self {
+        // SAFETY: `timer` is always the `timer` field of a live `LedData`, per the invariant
+        // that `LedData::drop` cancels it before the struct is torn down.
+        unsafe { container_of!(timer, Self, timer) }
+    }
+
+    fn on_timer(&self) -> HrTimerRestart {
+        let period_ms = {
+            let mut inner = self.inner.lock();
+            if inner.blink_period_ms == 0 {
+                return HrTimerRestart::NoRestart;
+            }
+            let next_on = !inner.blink_on;
+            inner.led.set_brightness(if next_on { inner.brightness } else { 0 });
+            inner.blink_on = next_on;
+            // `brightness` (the only state `read()` reports) doesn't change across a blink tick,
+            // so `generation` isn't bumped and blocked readers aren't woken for it.
+            inner.blink_period_ms
+        };
+        // SAFETY: `self` is kept alive by the same invariant as above, so `self.timer` is still
+        // valid to rearm from within its own callback.
+        unsafe {
+            Pin::new_unchecked(&self.timer).start::<Self>(Duration::from_millis(period_ms as u64));
+        }
+        HrTimerRestart::NoRestart
+    }
+}
+
+impl Drop for LedData {
+    fn drop(&mut self) {
+        // The critical invariant: no timer callback may still be running (and therefore no
+        // `unsafe impl HrTimerCallback::from_timer` dereference of `self` may still be in
+        // flight) once `self.inner`/`self.state_changed` start being torn down.
+        self.timer.cancel();
+    }
+}
+
+/// Per-open handle: tracks which [`LedStateInner::generation`] this file descriptor has already
+/// observed, so a blocking [`file::Operations::read`] only wakes up for changes it hasn't seen yet.
+struct LedHandle {
+    shared: Arc<LedData>,
+    last_seen: AtomicU64,
+}
+
+impl LedHandle {
+    /// Drives the LED to `brightness` (0 = off, 255 = full-on), stopping any blink in progress,
+    /// and wakes any blocked readers. Shared by the `write` path and the `LED_SET_STATE`/
+    /// `LED_TOGGLE` ioctls.
+    fn set_brightness(&self, brightness: u8) {
+        self.shared.timer.cancel();
+        let mut inner = self.shared.inner.lock();
+        inner.blink_period_ms = 0;
+        inner.led.set_brightness(brightness);
+        inner.brightness = brightness;
+        inner.generation += 1;
+        drop(inner);
+        self.shared.state_changed.notify_all();
+    }
+
+    /// Starts (or retargets) a hardware-timer blink with the given half-period, or stops
+    /// blinking and goes solid at the current brightness if `period_ms` is `0`.
+    fn set_blink(&self, period_ms: u32) {
+        self.shared.timer.cancel();
+        let mut inner = self.shared.inner.lock();
+        inner.blink_period_ms = period_ms;
+        inner.blink_on = true;
+        let brightness = inner.brightness;
+        inner.led.set_brightness(brightness);
+        drop(inner);
+        if period_ms == 0 {
+            return;
+        }
+        // SAFETY: `self.shared` is a pinned `Arc<LedData>` that outlives the timer, since
+        // `LedData::drop` cancels it before any other field is torn down.
+        unsafe {
+            Pin::new_unchecked(&self.shared.timer)
+                .start::<LedData>(Duration::from_millis(period_ms as u64));
+        }
+    }
+}
+
 struct RustFile;
 
 #[vtable]
 impl file::Operations for RustFile {
-    type Data = Arc<LedData>;
+    type Data = Arc<LedHandle>;
     type OpenData = Arc<LedData>;
 
     fn open(shared: &Arc<LedData>, _file: &file::File) -> Result<Self::Data> {
         pr_info!("open in led device\n",);
 
-        return Ok(shared.clone());
+        let generation = shared.inner.lock().generation;
+        Ok(Arc::try_new(LedHandle {
+            shared: shared.clone(),
+            last_seen: AtomicU64::new(generation),
+        })?)
     }
 
     fn read(
-        shared: ArcBorrow<'_, LedData>,
-        _file: &File,
+        handle: ArcBorrow<'_, LedHandle>,
+        file: &File,
         writer: &mut impl IoBufferWriter,
         offset: u64,
     ) -> Result<usize> {
-        Ok(0)
+        if offset != 0 {
+            return Ok(0);
+        }
+
+        let last_seen = handle.last_seen.load(Ordering::Relaxed);
+        let mut inner = handle.shared.inner.lock();
+
+        if file.flags() & bindings::O_NONBLOCK == 0 {
+            while inner.generation == last_seen {
+                if handle.shared.state_changed.wait(&mut inner) {
+                    return Err(EINTR);
+                }
+            }
+        }
+
+        handle.last_seen.store(inner.generation, Ordering::Relaxed);
+        let brightness = inner.brightness;
+        drop(inner);
+
+        let line = CString::try_from_fmt(fmt!("{}\n", brightness))?;
+        writer.write_slice(line.as_bytes())?;
+        Ok(line.as_bytes().len())
     }
 
     fn write(
-        shared: ArcBorrow<'_, LedData>,
+        handle: ArcBorrow<'_, LedHandle>,
         _file: &File,
         reader: &mut impl IoBufferReader,
-        offset: u64,
+        _offset: u64,
     ) -> Result<usize> {
-        let mut led = shared.deref().led.lock();
         let input = reader.read_all()?;
-        if input.len() != 2 {
-            return Err(EINVAL);
-        }
-        match input[0] {
-            b'0' => led.off(),
-            b'1' => led.on(),
-            _ => return Err(EINVAL),
-        }
+        // A decimal brightness in 0..=255; "0" is still off, full-on is now "255" (the old "1"
+        // write now sets brightness 1).
+        let text = core::str::from_utf8(&input).map_err(|_| EINVAL)?.trim();
+        let brightness: u8 = text.parse().map_err(|_| EINVAL)?;
+
+        handle.set_brightness(brightness);
+
         Ok(input.len())
     }
 
+    fn ioctl(handle: ArcBorrow<'_, LedHandle>, file: &File, cmd: &mut IoctlCommand) -> Result<i32> {
+        cmd.dispatch::<Self>(handle, file)
+    }
+
     fn release(_data: Self::Data, _file: &File) {
         pr_info!("release in led device\n");
     }
 }
 
+impl IoctlHandler for RustFile {
+    type Target<'a> = ArcBorrow<'a, LedHandle>;
+
+    fn read(
+        handle: ArcBorrow<'_, LedHandle>,
+        _file: &File,
+        cmd: u32,
+        writer: &mut impl IoBufferWriter,
+    ) -> Result<i32> {
+        match cmd {
+            LED_GET_STATE => {
+                let brightness = handle.shared.inner.lock().brightness;
+                writer.write_slice(&[brightness])?;
+                Ok(0)
+            }
+            _ => Err(EINVAL),
+        }
+    }
+
+    fn write(
+        handle: ArcBorrow<'_, LedHandle>,
+        _file: &File,
+        cmd: u32,
+        reader: &mut impl IoBufferReader,
+    ) -> Result<i32> {
+        match cmd {
+            LED_SET_STATE => {
+                let buf = reader.read_all()?;
+                if buf.len() != 1 {
+                    return Err(EINVAL);
+                }
+                handle.set_brightness(buf[0]);
+                Ok(0)
+            }
+            LED_SET_BLINK => {
+                let buf = reader.read_all()?;
+                if buf.len() != 4 {
+                    return Err(EINVAL);
+                }
+                let period_ms = u32::from_ne_bytes([buf[0], buf[1], buf[2], buf[3]]);
+                handle.set_blink(period_ms);
+                Ok(0)
+            }
+            _ => Err(EINVAL),
+        }
+    }
+
+    fn pure(handle: ArcBorrow<'_, LedHandle>, _file: &File, cmd: u32, _arg: usize) -> Result<i32> {
+        match cmd {
+            LED_TOGGLE => {
+                let brightness = if handle.shared.inner.lock().brightness == 0 {
+                    u8::MAX
+                } else {
+                    0
+                };
+                handle.set_brightness(brightness);
+                Ok(0)
+            }
+            _ => Err(EINVAL),
+        }
+    }
+}
+
 struct RustLed {
-    _dev: Pin<Box<miscdev::Registration<RustFile>>>,
+    _devs: Vec<Pin<Box<miscdev::Registration<RustFile>>>>,
 }
 
 impl kernel::Module for RustLed {
     fn init(_module: &'static ThisModule) -> Result<Self> {
         pr_info!("Rust Led init\n");
 
-        let data: Arc<LedData> = LedData::try_new()?;
-
-        let reg = miscdev::Registration::new_pinned(fmt!("rust_led"), data)?;
+        let mut devs = Vec::new();
+        for (i, &gpio) in LED_GPIOS.iter().enumerate() {
+            let data: Arc<LedData> = LedData::try_new(gpio)?;
+            let reg = miscdev::Registration::new_pinned(fmt!("rust_led{}", i), data)?;
+            devs.try_push(reg)?;
+        }
 
-        Ok(RustLed { _dev: reg })
+        Ok(RustLed { _devs: devs })
     }
 }
 
@@ -4,6 +4,7 @@
 //!
 //! Based on the C driver written by ARM Ltd/Deep Blue Solutions Ltd.
 
+use core::cell::Cell;
 use core::ops::DerefMut;
 use kernel::{
     amba,
@@ -17,9 +18,9 @@ use kernel::{
     module_amba_driver, new_device_data, new_mutex_pinned,
     prelude::*,
     serial::{
-        ktermbits::Ktermios,
+        ktermbits::{Config, DataBits, Ktermios, Parity, StopBits},
         pl011_config::*,
-        tty::SerialStruct,
+        tty::{SerialStruct, TtyFlag},
         uart_console::{self, flags, Console, ConsoleOps},
         uart_driver::UartDriver,
         uart_port::{PortRegistration, UartPort, UartPortOps},
@@ -36,6 +37,18 @@ pub const UPF_BOOT_AUTOCONF: u64 = 1_u64 << 28;
 pub(crate) const UART_NR: usize = 14;
 const AMBA_MAJOR: i32 = 204;
 const AMBA_MINOR: i32 = 64;
+
+/// Ceiling on the number of ports once we've fallen back to a dynamically-allocated major
+/// (see [`Ports::find_free_port`] and the registration in [`PL011Device::probe`]). Chosen the
+/// same way uartlite sizes its port table when its fixed instance count isn't enough.
+const UART_NR_MAX: usize = 64;
+
+/// Set to force dynamic major allocation from the first probe onward, for systems that are known
+/// ahead of time to need more than `UART_NR` AMBA UARTs (or that already have another driver on
+/// major 204). Otherwise the first probe registers on the fixed 204/64 pair, and ports beyond
+/// `UART_NR` are rejected.
+const FORCE_DYNAMIC_MAJOR: bool = false;
+
 const DEV_NAME: &CStr = c_str!("ttyAMA");
 const DRIVER_NAME: &CStr = c_str!("ttyAMA");
 
@@ -93,8 +106,13 @@ static PL0111_STD_OFFSETS: [u32; Regs::RegArraySize as usize] = {
 struct Ports(Vec<Option<Arc<PL011DeviceData>>>);
 
 impl Ports {
+    /// Finds a free port slot, growing past the statically reserved [`UART_NR`] minors (up to
+    /// [`UART_NR_MAX`]) instead of refusing once that fixed range is full. [`PL011Device::probe`]
+    /// only accepts an index outside `UART_NR` when [`UART_DRIVER`] was registered with a
+    /// dynamically allocated major (see [`FORCE_DYNAMIC_MAJOR`]); otherwise the driver is already
+    /// registered on the fixed 204/64 range by then and probe rejects the port with `ENOSPC`.
     fn find_free_port(&self) -> Option<usize> {
-        if self.0.len() >= UART_NR {
+        if self.0.len() >= UART_NR_MAX {
             return None;
         }
         for i in 0..self.0.len() {
@@ -151,7 +169,7 @@ pub(crate) static UART_DRIVER: UartDriver =
         UART_NR as _,
     );
 
-struct PL011UartPort<'a>(pub(crate) &'a mut UartPort);
+struct PL011UartPort<'a>(pub(crate) &'a UartPort);
 impl PL011UartPort<'_> {
     fn write(&self, val: u32, reg: Regs) {
         dbg!("write {} to {:?}", val, reg.clone());
@@ -174,6 +192,15 @@ impl PL011UartPort<'_> {
             iomem.try_readw_relaxed(offset).unwrap().into()
         }
     }
+    /// The `im` field of `PL011Data` is only ever touched with `port->lock` held (by core
+    /// serial_core code, or by us inside the IRQ handler); it's a `Cell` rather than a plain
+    /// `u32` so that serialization is real interior mutability instead of a pointer cast away
+    /// from the shared `&PL011DeviceData`.
+    fn set_im(&self, data: &PL011DeviceData, im: u32) {
+        data.im.set(im);
+        self.write(im, Regs::RegImsc);
+    }
+
     fn console_putchar(&self, ch: u8) {
         while (self.read(Regs::RegFr) & UART01X_FR_TXFF) != 0 {
             cpu_relax();
@@ -183,10 +210,357 @@ impl PL011UartPort<'_> {
     fn console_write(&self, s: *const u8, count: u32) {
         for i in 0..count {
             let ch = unsafe { *s.offset(i.try_into().unwrap()) };
-            if ch == '\n' as u8 {
-                self.console_putchar(ch);
+            if ch == b'\n' {
+                self.console_putchar(b'\r');
+            }
+            self.console_putchar(ch);
+        }
+    }
+
+    /// Feeds a received byte through the magic-SysRq handler using the underscore-prefixed
+    /// multi-letter form (e.g. `_reisub`, which walks `r`, `e`, `i`, `s`, `u`, `b` in turn): a `_`
+    /// arms it, each following alphanumeric byte is handed to `handle_sysrq`, and the first other
+    /// byte disarms it. A break condition does not arm it. Returns `true` when the byte was
+    /// consumed as part of a SysRq sequence and should not be pushed to the tty.
+    fn handle_sysrq(&self, data: &PL011DeviceData, ch: u8) -> bool {
+        if !data.sysrq_armed.get() {
+            if ch == b'_' {
+                self.set_sysrq_armed(data, true);
+                return true;
+            }
+            return false;
+        }
+
+        if ch.is_ascii_alphanumeric() {
+            unsafe { bindings::handle_sysrq(ch as core::ffi::c_int) };
+            return true;
+        }
+
+        self.set_sysrq_armed(data, false);
+        false
+    }
+
+    /// The `sysrq_armed` field of `PL011Data` is only ever touched from the RX path with
+    /// `port->lock` held; it's a `Cell`, same as [`PL011Data::im`], so that holds as real
+    /// interior mutability rather than a pointer cast away from the shared `&PL011DeviceData`.
+    fn set_sysrq_armed(&self, data: &PL011DeviceData, armed: bool) {
+        data.sysrq_armed.set(armed);
+    }
+
+    /// Drains the RX FIFO into the tty flip buffer, decoding the error bits carried in the
+    /// upper half of `RegDr`.
+    fn rx_chars(&self, data: &PL011DeviceData) {
+        while (self.read(Regs::RegFr) & UART01X_FR_RXFE) == 0 {
+            let dr = self.read(Regs::RegDr);
+            let ch = (dr & 0xff) as u8;
+
+            if self.handle_sysrq(data, ch) {
+                continue;
+            }
+
+            let mut flag = TtyFlag::Normal;
+            if (dr & UART011_DR_BE) != 0 {
+                flag = TtyFlag::Break;
+            } else if (dr & UART011_DR_PE) != 0 {
+                flag = TtyFlag::Parity;
+            } else if (dr & UART011_DR_FE) != 0 {
+                flag = TtyFlag::Frame;
             }
+            let overrun = (dr & UART011_DR_OE) != 0;
+
+            self.0.insert_char(ch, overrun, flag);
         }
+        self.0.flip_buffer_push();
+    }
+
+    /// Fills the TX FIFO from the port's circular xmit buffer, stopping TX once it runs dry.
+    fn tx_chars(&self, data: &PL011DeviceData) {
+        if self.0.tx_stopped() {
+            return;
+        }
+
+        if data.vendor.dma_threshold {
+            if let Some(resources) = data.resources() {
+                if self.dma_tx_chars(data, resources) {
+                    return;
+                }
+            }
+        }
+
+        let mut sent = false;
+        self.0.for_each_pending_tx(|ch| {
+            if (self.read(Regs::RegFr) & UART01X_FR_TXFF) != 0 {
+                return false;
+            }
+            self.write(ch.into(), Regs::RegDr);
+            sent = true;
+            true
+        });
+
+        if sent {
+            self.0.write_wakeup();
+        }
+
+        if self.0.circ_is_empty() {
+            self.rs485_stop_tx(data);
+            PL011Device::stop_tx(self.0);
+        }
+    }
+
+    /// Asserts the RTS/DE line for an RS485 transceiver before filling the FIFO, honoring the
+    /// configured RTS-on-send polarity and the `delay_rts_before_send` settle time. A no-op when
+    /// RS485 mode isn't enabled.
+    fn rs485_start_tx(&self, data: &PL011DeviceData) {
+        let rs485 = data.rs485.get();
+        if (rs485.flags & bindings::SER_RS485_ENABLED) == 0 {
+            return;
+        }
+
+        let mut mctrl = PL011Device::get_mctrl(self.0) & !bindings::TIOCM_RTS;
+        if (rs485.flags & bindings::SER_RS485_RTS_ON_SEND) != 0 {
+            mctrl |= bindings::TIOCM_RTS;
+        }
+        PL011Device::set_mctrl(self.0, mctrl);
+
+        if rs485.delay_rts_before_send != 0 {
+            unsafe { bindings::mdelay(rs485.delay_rts_before_send as u64) };
+        }
+    }
+
+    /// De-asserts the RTS/DE line and re-enables the receiver once TX has drained, after the
+    /// configured `delay_rts_after_send`. A no-op when RS485 mode isn't enabled.
+    fn rs485_stop_tx(&self, data: &PL011DeviceData) {
+        let rs485 = data.rs485.get();
+        if (rs485.flags & bindings::SER_RS485_ENABLED) == 0 {
+            return;
+        }
+
+        while (self.read(Regs::RegFr) & data.vendor.fr_busy) != 0 {
+            cpu_relax();
+        }
+        if rs485.delay_rts_after_send != 0 {
+            unsafe { bindings::mdelay(rs485.delay_rts_after_send as u64) };
+        }
+
+        let mut mctrl = PL011Device::get_mctrl(self.0) & !bindings::TIOCM_RTS;
+        if (rs485.flags & bindings::SER_RS485_RTS_AFTER_SEND) != 0 {
+            mctrl |= bindings::TIOCM_RTS;
+        }
+        PL011Device::set_mctrl(self.0, mctrl);
+    }
+
+    /// Requests TX/RX slave DMA channels and arms the periodic RX transfer. Gated on
+    /// `data.vendor.dma_threshold`; leaves `resources.dma_tx_chan`/`dma_rx_chan` as `None` (the
+    /// FIFO/IRQ path stays in effect) when the platform has no usable DMA channels, matching the
+    /// way the C driver silently falls back.
+    ///
+    /// # Safety
+    ///
+    /// `resources` is only ever written here and in [`Self::dma_shutdown`], both of which run
+    /// outside of the hot TX/RX path while the port is being brought up or torn down.
+    fn dma_startup(&self, data: &PL011DeviceData, resources: &PL011Resources) {
+        let dev = self.0.get_dev().unwrap().as_raw();
+
+        let tx_chan = unsafe { bindings::dma_request_chan(dev, c_str!("tx").as_char_ptr()) };
+        if !is_err_ptr(tx_chan) {
+            let mut tx_buf_dma: bindings::dma_addr_t = 0;
+            let tx_buf = unsafe {
+                bindings::dma_alloc_coherent(
+                    dev,
+                    DMA_TX_BUF_SIZE as u64,
+                    &mut tx_buf_dma,
+                    bindings::GFP_KERNEL,
+                )
+            } as *mut u8;
+
+            if tx_buf.is_null() {
+                unsafe { bindings::dma_release_channel(tx_chan) };
+            } else {
+                resources.dma_tx_chan.set(Some(tx_chan));
+                resources.dma_tx_buf.set(tx_buf);
+                resources.dma_tx_buf_dma.set(tx_buf_dma);
+            }
+        }
+
+        let rx_chan = unsafe { bindings::dma_request_chan(dev, c_str!("rx").as_char_ptr()) };
+        if is_err_ptr(rx_chan) {
+            return;
+        }
+
+        let mut rx_buf_dma: bindings::dma_addr_t = 0;
+        let rx_buf = unsafe {
+            bindings::dma_alloc_coherent(
+                dev,
+                DMA_RX_BUF_SIZE as u64,
+                &mut rx_buf_dma,
+                bindings::GFP_KERNEL,
+            )
+        } as *mut u8;
+        if rx_buf.is_null() {
+            unsafe { bindings::dma_release_channel(rx_chan) };
+            return;
+        }
+
+        resources.dma_rx_chan.set(Some(rx_chan));
+        resources.dma_rx_buf.set(rx_buf);
+        resources.dma_rx_buf_dma.set(rx_buf_dma);
+
+        self.dma_rx_submit(data, resources);
+        self.set_im(data, data.im.get() | UART011_RXIM | UART011_RTIM);
+        self.write(self.read(Regs::RegDmacr) | UART011_DMACR_RXDMAE, Regs::RegDmacr);
+    }
+
+    /// Releases whatever DMA channels/buffers [`Self::dma_startup`] managed to acquire.
+    fn dma_shutdown(&self, resources: &PL011Resources) {
+        self.write(
+            self.read(Regs::RegDmacr) & !(UART011_DMACR_TXDMAE | UART011_DMACR_RXDMAE),
+            Regs::RegDmacr,
+        );
+
+        if let Some(chan) = resources.dma_tx_chan.get() {
+            unsafe { bindings::dmaengine_terminate_sync(chan) };
+            unsafe { bindings::dma_release_channel(chan) };
+            unsafe {
+                bindings::dma_free_coherent(
+                    self.0.get_dev().unwrap().as_raw(),
+                    DMA_TX_BUF_SIZE as u64,
+                    resources.dma_tx_buf.get() as *mut core::ffi::c_void,
+                    resources.dma_tx_buf_dma.get(),
+                )
+            };
+            resources.dma_tx_chan.set(None);
+        }
+        if let Some(chan) = resources.dma_rx_chan.get() {
+            unsafe { bindings::dmaengine_terminate_sync(chan) };
+            unsafe { bindings::dma_release_channel(chan) };
+            unsafe {
+                bindings::dma_free_coherent(
+                    self.0.get_dev().unwrap().as_raw(),
+                    DMA_RX_BUF_SIZE as u64,
+                    resources.dma_rx_buf.get() as *mut core::ffi::c_void,
+                    resources.dma_rx_buf_dma.get(),
+                )
+            };
+            resources.dma_rx_chan.set(None);
+        }
+    }
+
+    /// Gathers up to `DMA_TX_BUF_SIZE` pending xmit bytes and, if there are more than
+    /// `DMA_TX_THRESHOLD` of them, hands them to the TX DMA channel as a single transfer with
+    /// [`UART011_DMACR_TXDMAE`] set; otherwise (or when the transfer cannot be prepared) pushes
+    /// the (already-dequeued) bytes straight through the FIFO. Returns `true` when it fully handled the pending data (either way), so
+    /// the caller's FIFO loop in [`Self::tx_chars`] can be skipped.
+    fn dma_tx_chars(&self, data: &PL011DeviceData, resources: &PL011Resources) -> bool {
+        let Some(chan) = resources.dma_tx_chan.get() else {
+            return false;
+        };
+
+        let scratch = unsafe {
+            core::slice::from_raw_parts_mut(resources.dma_tx_buf.get(), DMA_TX_BUF_SIZE)
+        };
+        let mut n = 0usize;
+        self.0.for_each_pending_tx(|ch| {
+            if n >= scratch.len() {
+                return false;
+            }
+            scratch[n] = ch;
+            n += 1;
+            true
+        });
+
+        if n == 0 {
+            return false;
+        }
+
+        let desc = if n > DMA_TX_THRESHOLD {
+            unsafe {
+                bindings::dmaengine_prep_slave_single(
+                    chan,
+                    resources.dma_tx_buf_dma.get(),
+                    n as u64,
+                    bindings::DMA_MEM_TO_DEV,
+                    bindings::DMA_PREP_INTERRUPT,
+                )
+            }
+        } else {
+            core::ptr::null_mut()
+        };
+        if desc.is_null() {
+            for &ch in &scratch[..n] {
+                while (self.read(Regs::RegFr) & UART01X_FR_TXFF) != 0 {
+                    cpu_relax();
+                }
+                self.write(ch.into(), Regs::RegDr);
+            }
+            self.0.write_wakeup();
+            return true;
+        }
+
+        unsafe {
+            (*desc).callback = Some(pl011_dma_tx_callback);
+            (*desc).callback_param = self.0 as *const UartPort as *mut core::ffi::c_void;
+            bindings::dmaengine_submit(desc);
+            bindings::dma_async_issue_pending(chan);
+        }
+
+        self.write(self.read(Regs::RegDmacr) | UART011_DMACR_TXDMAE, Regs::RegDmacr);
+        self.0.write_wakeup();
+        true
+    }
+
+    /// (Re)submits the cyclic RX DMA transfer into `resources.dma_rx_buf`.
+    fn dma_rx_submit(&self, _data: &PL011DeviceData, resources: &PL011Resources) {
+        let Some(chan) = resources.dma_rx_chan.get() else {
+            return;
+        };
+
+        let desc = unsafe {
+            bindings::dmaengine_prep_dma_cyclic(
+                chan,
+                resources.dma_rx_buf_dma.get(),
+                DMA_RX_BUF_SIZE as u64,
+                DMA_RX_BUF_SIZE as u64,
+                bindings::DMA_DEV_TO_MEM,
+                bindings::DMA_PREP_INTERRUPT,
+            )
+        };
+        if desc.is_null() {
+            return;
+        }
+
+        unsafe {
+            (*desc).callback = Some(pl011_dma_rx_callback);
+            (*desc).callback_param = self.0 as *const UartPort as *mut core::ffi::c_void;
+            bindings::dmaengine_submit(desc);
+            bindings::dma_async_issue_pending(chan);
+        }
+    }
+
+    /// Flushes whatever the RX DMA channel has written into the bounce buffer into the tty flip
+    /// buffer, then re-arms the cyclic transfer. Called both from the periodic DMA callback and
+    /// from the RX-timeout interrupt, so idle partial lines aren't held up waiting for the buffer
+    /// to fill.
+    fn dma_rx_flush(&self, data: &PL011DeviceData, resources: &PL011Resources) {
+        let Some(chan) = resources.dma_rx_chan.get() else {
+            return;
+        };
+
+        let mut state = bindings::dma_tx_state::default();
+        unsafe { bindings::dmaengine_pause(chan) };
+        unsafe { bindings::dmaengine_tx_status(chan, 0, &mut state) };
+        let residue = state.residue as usize;
+        let available = DMA_RX_BUF_SIZE.saturating_sub(residue.min(DMA_RX_BUF_SIZE));
+
+        if available > 0 {
+            let buf = unsafe { core::slice::from_raw_parts(resources.dma_rx_buf.get(), available) };
+            for &ch in buf {
+                self.0.insert_char(ch, false, TtyFlag::Normal);
+            }
+            self.0.flip_buffer_push();
+        }
+
+        unsafe { bindings::dmaengine_terminate_sync(chan) };
+        self.dma_rx_submit(data, resources);
     }
 }
 
@@ -262,24 +636,109 @@ pub(crate) static VENDOR_DATA: VendorData = VendorData {
     fixfixed_options: false,
 };
 
-#[derive(Copy, Clone)]
 struct PL011Data {
-    im: u32,
+    /// Shadow of the `UART011_IMSC` register; only ever touched with `port->lock` held (by core
+    /// serial_core code, or by us inside the IRQ handler), so a `Cell` is enough serialization —
+    /// no need for a real lock on top of the one the caller already holds.
+    im: Cell<u32>,
     old_status: u32,
     fifosize: u32,
     // fixed_baud: u32,
     type_: &'static str,
     vendor: &'static VendorData,
+    /// Only ever touched from [`PL011UartPort::ioctl`] with `port->lock` held; same `Cell`
+    /// rationale as [`Self::im`].
+    rs485: Cell<bindings::serial_rs485>,
+    /// Whether the RX path has seen a `_` and is waiting for the SysRq letter(s) that follow it.
+    /// Only ever touched from the RX path with `port->lock` held; same `Cell` rationale as
+    /// [`Self::im`].
+    sysrq_armed: Cell<bool>,
 }
 
+/// Per-write size, in bytes, above which pending TX data is handed to the DMA engine instead of
+/// walked through the FIFO one byte at a time. Separate from `VendorData::dma_threshold`, which
+/// is the coarser "does this vendor want DMA at all" gate.
+const DMA_TX_THRESHOLD: usize = 8;
+/// Largest single TX DMA transfer gathered out of the xmit buffer per call.
+const DMA_TX_BUF_SIZE: usize = 256;
+/// Size of the periodic RX DMA bounce buffer.
+const DMA_RX_BUF_SIZE: usize = 4096;
+
 struct PL011Resources {
     base: IoMem<UART_SIZE>,
     parent_irq: u32,
+    /// Set by [`PL011UartPort::dma_startup`], cleared by [`PL011UartPort::dma_shutdown`] and
+    /// read everywhere else; `Cell`s so that
+    /// holds as real interior mutability instead of a pointer cast away from the shared
+    /// `&PL011Resources`.
+    dma_tx_chan: Cell<Option<*mut bindings::dma_chan>>,
+    dma_rx_chan: Cell<Option<*mut bindings::dma_chan>>,
+    dma_tx_buf: Cell<*mut u8>,
+    dma_tx_buf_dma: Cell<bindings::dma_addr_t>,
+    dma_rx_buf: Cell<*mut u8>,
+    dma_rx_buf_dma: Cell<bindings::dma_addr_t>,
+}
+
+/// Mirrors the kernel's `IS_ERR`: dmaengine channel-request helpers return an error encoded as a
+/// small negative value cast to a pointer rather than `NULL`.
+fn is_err_ptr<T>(ptr: *mut T) -> bool {
+    (ptr as usize) >= (usize::MAX - 4095)
 }
 
 type PL011Registrations = PortRegistration<PL011Device>;
 type PL011DeviceData = device::Data<PL011Registrations, PL011Resources, PL011Data>;
 
+/// Top-half IRQ handler: drains the RX FIFO and tops up the TX FIFO for the port whose `UartPort`
+/// pointer was handed to `request_irq` as `dev_id` in `PL011Device::startup`.
+unsafe extern "C" fn pl011_interrupt(
+    _irq: core::ffi::c_int,
+    dev_id: *mut core::ffi::c_void,
+) -> bindings::irqreturn_t {
+    let port = UartPort::from_ptr(dev_id as *mut bindings::uart_port);
+    let p = PL011UartPort(port);
+    let data = port.get_data::<PL011DeviceData>();
+
+    let status = p.read(Regs::RegMis);
+    if status == 0 {
+        return bindings::irqreturn_IRQ_NONE;
+    }
+
+    p.write(status, Regs::RegIcr);
+
+    let resources = port.get_data::<PL011DeviceData>().resources().unwrap();
+    if (status & UART011_RTIS) != 0 && resources.dma_rx_chan.get().is_some() {
+        p.dma_rx_flush(data, resources);
+    } else if (status & (UART011_RXIS | UART011_RTIS)) != 0 {
+        p.rx_chars(data);
+    }
+    if (status & UART011_TXIS) != 0 {
+        p.tx_chars(data);
+    }
+
+    bindings::irqreturn_IRQ_HANDLED
+}
+
+/// Completion callback for a single TX DMA transfer: clears `UART011_DMACR_TXDMAE` and hands
+/// control back to [`PL011UartPort::tx_chars`] to keep draining the xmit buffer (either via
+/// another DMA transfer or the FIFO, depending on how much is left).
+unsafe extern "C" fn pl011_dma_tx_callback(param: *mut core::ffi::c_void) {
+    let port = unsafe { &mut *(param as *mut UartPort) };
+    let p = PL011UartPort(port);
+    let data = port.get_data::<PL011DeviceData>();
+    p.write(p.read(Regs::RegDmacr) & !UART011_DMACR_TXDMAE, Regs::RegDmacr);
+    p.tx_chars(data);
+}
+
+/// Cyclic-period completion callback for the RX DMA channel: flushes the bounce buffer to the
+/// tty and re-arms the transfer.
+unsafe extern "C" fn pl011_dma_rx_callback(param: *mut core::ffi::c_void) {
+    let port = unsafe { &mut *(param as *mut UartPort) };
+    let p = PL011UartPort(port);
+    let data = port.get_data::<PL011DeviceData>();
+    let resources = data.resources().unwrap();
+    p.dma_rx_flush(data, resources);
+}
+
 // Linux Raw id table
 kernel::define_amba_id_table! {MY_AMDA_ID_TABLE, (), [
     ({id: 0x00041011, mask: 0x000fffff}, None),
@@ -291,22 +750,62 @@ struct PL011Device;
 #[vtable]
 impl UartPortOps for PL011Device {
     type Data = Arc<PL011DeviceData>;
-    fn tx_empty(_: &UartPort) -> u32 {
-        dbg!("tx_empty\n");
-        0
+    fn tx_empty(port: &UartPort) -> u32 {
+        let port = PL011UartPort(port);
+        if (port.read(Regs::RegFr) & UART01X_FR_BUSY) == 0 {
+            bindings::TIOCSER_TEMT
+        } else {
+            0
+        }
     }
-    fn set_mctrl(_: &UartPort, _: u32) {
-        dbg!("set_mctrl\n");
+    fn set_mctrl(port: &UartPort, mctrl: u32) {
+        let p = PL011UartPort(port);
+        let mut cr = p.read(Regs::RegCr);
+
+        cr &= !(UART011_CR_RTS | UART011_CR_DTR | UART011_CR_OUT1 | UART011_CR_OUT2);
+        if (mctrl & bindings::TIOCM_RTS) != 0 {
+            cr |= UART011_CR_RTS;
+        }
+        if (mctrl & bindings::TIOCM_DTR) != 0 {
+            cr |= UART011_CR_DTR;
+        }
+        if (mctrl & bindings::TIOCM_OUT1) != 0 {
+            cr |= UART011_CR_OUT1;
+        }
+        if (mctrl & bindings::TIOCM_OUT2) != 0 {
+            cr |= UART011_CR_OUT2;
+        }
+
+        p.write(cr, Regs::RegCr);
     }
-    fn get_mctrl(_: &UartPort) -> u32 {
-        dbg!("get_mctrl\n");
-        0
+    fn get_mctrl(port: &UartPort) -> u32 {
+        let p = PL011UartPort(port);
+        let data = port.get_data::<PL011DeviceData>();
+        let fr = p.read(Regs::RegFr) ^ data.vendor.inv_fr;
+
+        let mut result = bindings::TIOCM_CAR | bindings::TIOCM_DSR | bindings::TIOCM_CTS;
+        if (fr & data.vendor.fr_dsr) == 0 {
+            result &= !bindings::TIOCM_DSR;
+        }
+        if (fr & data.vendor.fr_cts) == 0 {
+            result &= !bindings::TIOCM_CTS;
+        }
+        if (fr & data.vendor.fr_ri) != 0 {
+            result |= bindings::TIOCM_RI;
+        }
+        result
     }
-    fn stop_tx(_: &UartPort) {
-        dbg!("stop_tx\n");
+    fn stop_tx(port: &UartPort) {
+        let p = PL011UartPort(port);
+        let data = port.get_data::<PL011DeviceData>();
+        p.set_im(data, data.im.get() & !UART011_TXIM);
     }
-    fn start_tx(_: &UartPort) {
-        dbg!("start_tx\n");
+    fn start_tx(port: &UartPort) {
+        let p = PL011UartPort(port);
+        let data = port.get_data::<PL011DeviceData>();
+        p.rs485_start_tx(data);
+        p.set_im(data, data.im.get() | UART011_TXIM);
+        p.tx_chars(data);
     }
     fn throttle(_: &UartPort) {
         dbg!("throttle\n");
@@ -317,27 +816,113 @@ impl UartPortOps for PL011Device {
     fn send_xchar(_: &UartPort, _: i8) {
         dbg!("send_xchar\n");
     }
-    fn stop_rx(_: &UartPort) {
-        dbg!("stop_rx\n");
+    fn stop_rx(port: &UartPort) {
+        let p = PL011UartPort(port);
+        let data = port.get_data::<PL011DeviceData>();
+        p.set_im(data, data.im.get() & !(UART011_RXIM | UART011_RTIM));
     }
-    fn start_rx(_: &UartPort) {
-        dbg!("start_rx\n");
+    fn start_rx(port: &UartPort) {
+        let p = PL011UartPort(port);
+        let data = port.get_data::<PL011DeviceData>();
+        p.set_im(data, data.im.get() | UART011_RXIM | UART011_RTIM);
     }
     fn break_ctl(_: &UartPort, _: i32) {
         dbg!("break_ctl\n");
     }
-    fn startup(_: &UartPort) -> i32 {
-        dbg!("startup\n");
+    fn startup(port: &UartPort) -> i32 {
+        let data = port.get_data::<PL011DeviceData>();
+        let resources = data.resources().unwrap();
+        let p = PL011UartPort(port);
+
+        p.write(data.vendor.ifls, Regs::RegIfls);
+        p.set_im(data, UART011_RXIM | UART011_RTIM);
+
+        let dev_id = port as *const UartPort as *mut core::ffi::c_void;
+        let ret = unsafe {
+            bindings::request_irq(
+                resources.parent_irq,
+                Some(pl011_interrupt),
+                0,
+                c_str!("uart-pl011").as_char_ptr(),
+                dev_id,
+            )
+        };
+        if ret != 0 {
+            return ret;
+        }
+
+        if data.vendor.dma_threshold {
+            p.dma_startup(data, resources);
+        }
+
         0
     }
-    fn shutdown(_: &UartPort) {
-        dbg!("shutdown\n");
+    fn shutdown(port: &UartPort) {
+        let data = port.get_data::<PL011DeviceData>();
+        let p = PL011UartPort(port);
+        p.set_im(data, 0);
+
+        let resources = data.resources().unwrap();
+        if data.vendor.dma_threshold {
+            p.dma_shutdown(resources);
+        }
+
+        let dev_id = port as *const UartPort as *mut core::ffi::c_void;
+        unsafe { bindings::free_irq(resources.parent_irq, dev_id) };
     }
     fn flush_buffer(_: &UartPort) {
         dbg!("flush_buffer\n");
     }
-    fn set_termios(_: &UartPort, _: &mut Ktermios, _: &Ktermios) {
-        dbg!("set_termios\n");
+    fn set_termios(port: &UartPort, new: &mut Ktermios, _old: &Ktermios) {
+        let p = PL011UartPort(port);
+        let data = port.get_data::<PL011DeviceData>();
+
+        let uartclk = port.get_dev().unwrap().clk_get().unwrap().get_rate();
+        let mut config = new.decode();
+
+        let max_baud = (uartclk / 16) as u32;
+        let min_baud = (uartclk / (16 * 65535)) as u32;
+        config.baud_rate = config.baud_rate.clamp(min_baud.max(1), max_baud);
+
+        let div16 = if data.vendor.oversampling { 8 } else { 16 };
+        let divisor = uartclk / (div16 * config.baud_rate as u64);
+        let remainder = uartclk % (div16 * config.baud_rate as u64);
+        let ibrd = divisor as u32;
+        let fbrd = ((remainder * 64 + (div16 * config.baud_rate as u64) / 2)
+            / (div16 * config.baud_rate as u64)) as u32;
+
+        let mut lcrh = match config.data_bits {
+            DataBits::Five => UART011_LCRH_WLEN_5,
+            DataBits::Six => UART011_LCRH_WLEN_6,
+            DataBits::Seven => UART011_LCRH_WLEN_7,
+            DataBits::Eight => UART011_LCRH_WLEN_8,
+        };
+        match config.parity {
+            Parity::None => {}
+            Parity::Even => lcrh |= UART011_LCRH_PEN | UART011_LCRH_EPS,
+            Parity::Odd => lcrh |= UART011_LCRH_PEN,
+        }
+        if config.stop_bits == StopBits::Two {
+            lcrh |= UART011_LCRH_STP2;
+        }
+        lcrh |= UART011_LCRH_FEN;
+
+        // Disable the UART, drain anything still in flight, then reprogram the divisor and
+        // line format before turning it back on.
+        let old_cr = p.read(Regs::RegCr);
+        p.write(old_cr & !UART01X_CR_UARTEN, Regs::RegCr);
+        while (p.read(Regs::RegFr) & UART01X_FR_BUSY) != 0 {
+            cpu_relax();
+        }
+
+        p.write(ibrd, Regs::RegIbrd);
+        p.write(fbrd, Regs::RegFbrd);
+        p.write(lcrh, Regs::RegLcrhRx);
+        p.write(lcrh, Regs::RegLcrhTx);
+
+        p.write(old_cr, Regs::RegCr);
+
+        new.apply(&config);
     }
     fn set_ldisc(_: &UartPort, _: &mut Ktermios) {
         dbg!("set_ldisc\n");
@@ -366,9 +951,46 @@ impl UartPortOps for PL011Device {
         dbg!("verify_port\n");
         0
     }
-    fn ioctl(_: &UartPort, _: u32, _: u64) -> i32 {
-        dbg!("ioctl\n");
-        0
+    fn ioctl(port: &UartPort, cmd: u32, arg: u64) -> i32 {
+        let data = port.get_data::<PL011DeviceData>();
+
+        match cmd {
+            bindings::TIOCSRS485 => {
+                let mut rs485 = bindings::serial_rs485::default();
+                let user = arg as *const core::ffi::c_void;
+                let ret = unsafe {
+                    bindings::copy_from_user(
+                        &mut rs485 as *mut _ as *mut core::ffi::c_void,
+                        user,
+                        core::mem::size_of::<bindings::serial_rs485>() as u64,
+                    )
+                };
+                if ret != 0 {
+                    return EFAULT.to_errno();
+                }
+                data.rs485.set(rs485);
+                0
+            }
+            bindings::TIOCGRS485 => {
+                let user = arg as *mut core::ffi::c_void;
+                let rs485 = data.rs485.get();
+                let ret = unsafe {
+                    bindings::copy_to_user(
+                        user,
+                        &rs485 as *const bindings::serial_rs485 as *const core::ffi::c_void,
+                        core::mem::size_of::<bindings::serial_rs485>() as u64,
+                    )
+                };
+                if ret != 0 {
+                    return EFAULT.to_errno();
+                }
+                0
+            }
+            _ => {
+                dbg!("ioctl\n");
+                ENOIOCTLCMD.to_errno()
+            }
+        }
     }
     fn poll_init(_: &UartPort) -> i32 {
         dbg!("poll_init\n");
@@ -429,13 +1051,21 @@ impl amba::Driver for PL011Device {
             PL011Resources {
                 base: reg_mem,
                 parent_irq: irq,
+                dma_tx_chan: Cell::new(None),
+                dma_rx_chan: Cell::new(None),
+                dma_tx_buf: Cell::new(core::ptr::null_mut()),
+                dma_tx_buf_dma: Cell::new(0),
+                dma_rx_buf: Cell::new(core::ptr::null_mut()),
+                dma_rx_buf_dma: Cell::new(0),
             },
             PL011Data {
-                im: 0,
+                im: Cell::new(0),
                 old_status: 0,
                 fifosize,
                 type_: "PL011",
                 vendor: &VENDOR_DATA,
+                rs485: Cell::new(bindings::serial_rs485::default()),
+                sysrq_armed: Cell::new(false),
             },
             "pl011"
         )?;
@@ -443,7 +1073,28 @@ impl amba::Driver for PL011Device {
         let arc_portdata: Arc<PL011DeviceData> = Arc::from(data);
 
         if !UART_DRIVER.is_registered() {
-            UART_DRIVER.register()?;
+            if FORCE_DYNAMIC_MAJOR || portnr >= UART_NR {
+                // Either asked for upfront, or we've already run past the statically reserved
+                // minors: hand registration a major of 0 so the tty core allocates one
+                // dynamically (its `alloc_chrdev_region`-style path) instead of colliding with
+                // whatever else is sitting on 204/64, and size `nr` to `UART_NR_MAX` so every
+                // index `find_free_port` can hand out fits.
+                dev_info!(
+                    adev,
+                    "registering {} with a dynamically allocated major (nr={})\n",
+                    DRIVER_NAME,
+                    UART_NR_MAX
+                );
+                UART_DRIVER.register_with(0, 0, UART_NR_MAX as i32)?;
+            } else {
+                UART_DRIVER.register()?;
+            }
+        } else if portnr >= UART_NR {
+            // The tty core sized its port table at registration time; there's no way to grow it
+            // after the fact. Systems expecting to exceed `UART_NR` should set
+            // `FORCE_DYNAMIC_MAJOR` so the very first registration already accounts for it.
+            dev_info!(adev, "port {} exceeds the range {} was registered with\n", portnr, DRIVER_NAME);
+            return Err(ENOSPC);
         }
         let mut registration = arc_portdata.registrations().ok_or(ENXIO)?;
         let registration_mut = unsafe { Pin::new_unchecked(registration.deref_mut()) };
@@ -467,6 +1118,60 @@ impl amba::Driver for PL011Device {
     }
 }
 
+/// Busy-waits on `UART01X_FR_TXFF` and pushes each byte of `s` to `RegDr`, for output before the
+/// full driver has probed. Reuses the same polling loop as [`Pl011Console::console_write`].
+unsafe extern "C" fn pl011_earlycon_write(
+    device: *mut bindings::console,
+    s: *const core::ffi::c_char,
+    count: core::ffi::c_uint,
+) {
+    let dev = unsafe { &*((*device).data as *const bindings::earlycon_device) };
+    let base = dev.port.membase as *mut u8;
+    let read_fr = || unsafe { core::ptr::read_volatile(base.add(UART01X_FR as usize) as *mut u32) };
+    let write_dr = |ch: u8| unsafe {
+        core::ptr::write_volatile(base.add(UART01X_DR as usize) as *mut u32, ch as u32)
+    };
+
+    for i in 0..count {
+        let ch = unsafe { *s.offset(i as isize) } as u8;
+        while (read_fr() & UART01X_FR_TXFF) != 0 {
+            cpu_relax();
+        }
+        write_dr(ch);
+    }
+}
+
+/// Parses the `earlycon=pl011,<addr>`/`stdout-path` options, maps just enough of the register
+/// window, and installs [`pl011_earlycon_write`] so the log has output from the earliest boot
+/// stages. Hands off cleanly once `PL011Device::probe` registers the real port.
+unsafe extern "C" fn pl011_earlycon_setup(
+    device: *mut bindings::earlycon_device,
+    options: *const core::ffi::c_char,
+) -> core::ffi::c_int {
+    let dev = unsafe { &mut *device };
+
+    if dev.port.membase.is_null() && dev.port.mapbase != 0 {
+        dev.port.membase =
+            unsafe { bindings::ioremap(dev.port.mapbase, UART_SIZE as u64) } as *mut _;
+    }
+    if dev.port.membase.is_null() {
+        return EINVAL.to_errno();
+    }
+
+    if !options.is_null() {
+        let len = unsafe { bindings::strnlen(options, 16) } as usize;
+        let opts = unsafe { core::slice::from_raw_parts(options as *const u8, len) };
+        if let Some(config) = Config::parse_earlycon_str(opts) {
+            dev.baud = config.baud_rate;
+        }
+    }
+
+    dev.con.write = Some(pl011_earlycon_write);
+    0
+}
+
+kernel::define_earlycon! {PL011_EARLYCON, "arm,pl011", pl011_earlycon_setup}
+
 module_amba_driver! {
     type: PL011Device,
     name: "pl011_uart_rust",
@@ -6,11 +6,19 @@
 
 use crate::{
     bindings,
-    error::{to_result, Result},
+    device::Device,
+    error::{code::EINVAL, to_result, Result},
+    str::CStr,
     types::Opaque,
 };
 use core::mem::ManuallyDrop;
 
+/// Whether `ptr` is one of the kernel's `ERR_PTR`-encoded error values, i.e. the top page's
+/// worth of addresses right below the end of the address space.
+fn is_err_ptr<T>(ptr: *mut T) -> bool {
+    (ptr as usize) >= (usize::MAX - 4095)
+}
+
 /// Represents `struct clk *`.
 ///
 /// # Invariants
@@ -30,12 +38,41 @@ impl Clk {
         self.0
     }
 
+    /// Looks up the clock named `name` (or the device's sole/default clock if `name` is `None`)
+    /// from `dev`'s device-tree node via `clk_get`. The returned `Clk` owns that reference and
+    /// releases it with `clk_put` when dropped.
+    pub fn get(dev: &Device, name: Option<&CStr>) -> Result<Self> {
+        let id = name.map_or(core::ptr::null(), |name| name.as_char_ptr());
+        // SAFETY: `dev.as_raw()` is a valid `struct device *` for the duration of this call, and
+        // `id` is either null or a valid NUL-terminated string outliving the call.
+        let clk = unsafe { bindings::clk_get(dev.as_raw(), id) };
+        if is_err_ptr(clk) {
+            return Err(EINVAL);
+        }
+        Ok(Self(clk))
+    }
+
     /// Get clk rate
     pub fn get_rate(&self) -> u64 {
         // SAFETY: call ffi and ptr is valid
         unsafe { bindings::clk_get_rate(self.0) }
     }
 
+    /// Reprograms the clock to `rate`, the way a UART driver does after picking an achievable
+    /// rate with [`Self::round_rate`].
+    pub fn set_rate(&self, rate: u64) -> Result {
+        // SAFETY: call ffi and ptr is valid
+        unsafe { to_result(bindings::clk_set_rate(self.0, rate)) }
+    }
+
+    /// Returns the rate the clock would actually run at if asked for `rate`, without changing
+    /// anything — lets a driver find the closest achievable baud-rate divisor before committing
+    /// to [`Self::set_rate`].
+    pub fn round_rate(&self, rate: u64) -> u64 {
+        // SAFETY: call ffi and ptr is valid
+        unsafe { bindings::clk_round_rate(self.0, rate) as u64 }
+    }
+
     /// clk enable
     pub fn prepare_enable(self) -> Result<EnabledClk> {
         // SAFETY: call ffi and ptr is valid
@@ -3,12 +3,14 @@ use crate::{
     str::CString,
     types::Opaque,
 };
+use alloc::boxed::Box;
 use bindings::{console, tty_driver};
 use core::{
     fmt::{self},
     marker::{self, PhantomData, PhantomPinned},
+    pin::Pin,
 };
-use kernel::error::{code, Error};
+use kernel::error::{code, to_result, Error};
 use macros::vtable;
 
 pub unsafe trait RawConsole {
@@ -114,6 +116,41 @@ impl<T> Console<T> {
     }
 }
 
+/// RAII registration of a [`Console`] with the kernel's console subsystem.
+///
+/// Calls `register_console` on construction and `unregister_console` in [`Drop`], so a
+/// `Registration` guarantees its `Console` is installed for exactly as long as it is alive; the
+/// `Console`'s own `PhantomPinned` guarantees the underlying `bindings::console` never moves out
+/// from under the core while it is registered.
+pub struct Registration<T> {
+    console: Pin<Box<Console<T>>>,
+}
+
+impl<T> Registration<T> {
+    /// Registers `console` with the kernel, taking ownership of it for the lifetime of the
+    /// registration.
+    pub fn new(console: Pin<Box<Console<T>>>) -> Result<Self> {
+        // SAFETY: `register_console` keeps a pointer to the `bindings::console` until
+        // `unregister_console` runs in `Drop`; the struct it points to is pinned and owned by
+        // `Self` for that whole time.
+        unsafe { to_result(bindings::register_console(console.raw_console())) }?;
+        Ok(Self { console })
+    }
+
+    /// Returns the registered [`Console`].
+    pub fn console(&self) -> &Console<T> {
+        &self.console
+    }
+}
+
+impl<T> Drop for Registration<T> {
+    fn drop(&mut self) {
+        // SAFETY: `self.console` was successfully passed to `register_console` in `new` and has
+        // not been unregistered yet.
+        unsafe { bindings::unregister_console(self.console.raw_console()) };
+    }
+}
+
 //  * @write:		Write callback to output messages (Optional)
 //  * @read:		Read callback for console input (Optional)
 //  * @device:		The underlying TTY device driver (Optional)
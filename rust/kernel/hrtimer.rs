@@ -0,0 +1,138 @@
+// SPDX-License-Identifier: GPL-2.0
+
+//! Intrusive high-resolution timers.
+//!
+//! Wraps `struct hrtimer` for drivers that need to reschedule themselves from (soft-)interrupt
+//! context, such as a software blink timer.
+//!
+//! C header: [`include/linux/hrtimer.h`](../../../../include/linux/hrtimer.h)
+
+use crate::{bindings, types::Opaque};
+use core::pin::Pin;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::time::Duration;
+
+/// What a fired timer's callback asks the core to do next.
+pub enum HrTimerRestart {
+    /// Leave the timer stopped.
+    NoRestart,
+    /// Re-enqueue the timer at its current expiry time; nothing here moves that expiry forward.
+    Restart,
+}
+
+impl HrTimerRestart {
+    fn as_raw(self) -> bindings::hrtimer_restart {
+        match self {
+            HrTimerRestart::NoRestart => bindings::hrtimer_restart_HRTIMER_NORESTART,
+            HrTimerRestart::Restart => bindings::hrtimer_restart_HRTIMER_RESTART,
+        }
+    }
+}
+
+/// Types that embed an [`HrTimer`] and can be driven by its repeating callback.
+pub trait HrTimerCallback {
+    /// Recovers `&Self` from a pointer to the `hrtimer` embedded in it.
+    ///
+    /// Implementors typically forward to [`crate::container_of!`] the same way
+    /// `AmbaUartPort::from_uart_port` recovers its owner from an embedded `UartPort`.
+    unsafe fn from_timer(timer: *mut bindings::hrtimer) -> *const Self;
+
+    /// Called from (soft-)interrupt context when the timer expires.
+    fn on_timer(&self) -> HrTimerRestart;
+}
+
+/// An embeddable, intrusive `struct hrtimer`.
+///
+/// # Invariants
+///
+/// Once [`Self::start`] has run, `self` is registered with the timer core and must not be moved
+/// or freed until a matching [`Self::cancel`] has returned.
+pub struct HrTimer {
+    timer: Opaque<bindings::hrtimer>,
+    /// Whether [`Self::start`] has already run `hrtimer_init` on `timer`.
+    initialized: AtomicBool,
+}
+
+impl HrTimer {
+    /// Creates a timer that is not yet armed.
+    ///
+    /// Call [`Self::start`] (which performs `hrtimer_init` on first use) before expecting it to
+    /// fire.
+    pub const fn new() -> Self {
+        Self {
+            timer: Opaque::uninit(),
+            initialized: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns a raw pointer to the inner C struct.
+    pub fn as_raw(&self) -> *mut bindings::hrtimer {
+        self.timer.get()
+    }
+
+    extern "C" fn run<T: HrTimerCallback>(timer: *mut bindings::hrtimer) -> bindings::hrtimer_restart {
+        // SAFETY: `timer` is the `hrtimer` embedded in a live `T`, per the type invariant that it
+        // is only ever started while its owner is pinned and alive.
+        let owner = unsafe { T::from_timer(timer) };
+        // SAFETY: `owner` was just recovered from a live, pinned `T`.
+        unsafe { &*owner }.on_timer().as_raw()
+    }
+
+    /// Initializes (on first use) and (re)arms the timer to fire `delay` from now, calling
+    /// `T::on_timer` when it does.
+    ///
+    /// `hrtimer_init` is only ever run once per timer: it isn't safe to repeat concurrently with
+    /// a `hrtimer_start_range_ns`/`hrtimer_cancel` touching the same `hrtimer` from another CPU,
+    /// which is exactly what can happen on rearm (the timer's own callback rearming itself while
+    /// another CPU cancels it). Every call after the first only rearms.
+    ///
+    /// # Safety
+    ///
+    /// `self` must be embedded in a `T` that will not move or be freed before [`Self::cancel`]
+    /// returns.
+    pub unsafe fn start<T: HrTimerCallback>(self: Pin<&Self>, delay: Duration) {
+        let raw = self.as_raw();
+        if !self.initialized.swap(true, Ordering::AcqRel) {
+            // SAFETY: `raw` points at a valid, pinned `hrtimer`, and `self.initialized` being
+            // `false` until just now means this is the only `hrtimer_init` call for this timer.
+            unsafe {
+                bindings::hrtimer_init(
+                    raw,
+                    bindings::CLOCK_MONOTONIC as _,
+                    bindings::hrtimer_mode_HRTIMER_MODE_REL,
+                );
+                (*raw).function = Some(Self::run::<T>);
+            }
+        }
+        // SAFETY: `raw` points at a valid, pinned `hrtimer` that has been through `hrtimer_init`
+        // either just above or on a prior call to `start`.
+        unsafe {
+            bindings::hrtimer_start_range_ns(
+                raw,
+                delay.as_nanos() as i64,
+                bindings::hrtimer_mode_HRTIMER_MODE_REL,
+            );
+        }
+    }
+
+    /// Cancels the timer and blocks until any in-flight callback has finished running, so the
+    /// caller can safely free the owning struct immediately afterwards. A timer that was never
+    /// started is left alone.
+    pub fn cancel(&self) {
+        if !self.initialized.load(Ordering::Acquire) {
+            return;
+        }
+        // SAFETY: `self.as_raw()` is a valid `hrtimer` that has been through `hrtimer_init`.
+        unsafe {
+            bindings::hrtimer_cancel(self.as_raw());
+        }
+    }
+}
+
+// SAFETY: `HrTimer` has no thread-affine state; the C core serializes callback invocations
+// against `start`/`cancel` itself.
+unsafe impl Send for HrTimer {}
+// SAFETY: All methods that touch the inner `hrtimer` go through FFI calls that are themselves
+// safe to invoke concurrently, and `initialized`'s `swap` lets racing `start` calls agree on
+// which single one runs `hrtimer_init`.
+unsafe impl Sync for HrTimer {}
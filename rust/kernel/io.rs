@@ -2,7 +2,12 @@ use bindings::iounmap;
 use core::{ffi::c_void, mem::size_of};
 
 /// A wrapper around `ioremap` and `iounmap`.
-/// 
+///
+/// MMIO registers must always be accessed with `read_volatile`/`write_volatile` (never a plain
+/// `Deref`, which the compiler is free to reorder or elide): use [`Self::read_reg`]/
+/// [`Self::write_reg`] to address registers by byte offset. These are plain volatile accesses
+/// with no memory barriers, like the kernel's `readl_relaxed`/`writel_relaxed`; they are not
+/// ordered against normal-memory accesses the way `readl`/`writel` are.
 pub struct IoReMapBox<T: Sized> {
     ptr: *mut T,
 }
@@ -17,6 +22,48 @@ impl<T: Sized> IoReMapBox<T> {
             Self { ptr }
         }
     }
+
+    /// Returns the base pointer of the mapped region.
+    pub fn as_ptr(&self) -> *mut T {
+        self.ptr
+    }
+
+    /// Performs a volatile read of the whole mapped value.
+    pub fn read_volatile(&self) -> T
+    where
+        T: Copy,
+    {
+        // SAFETY: `self.ptr` is a valid mapping for the lifetime of `self`.
+        unsafe { core::ptr::read_volatile(self.ptr) }
+    }
+
+    /// Performs a volatile write of the whole mapped value.
+    pub fn write_volatile(&self, val: T) {
+        // SAFETY: `self.ptr` is a valid mapping for the lifetime of `self`.
+        unsafe { core::ptr::write_volatile(self.ptr, val) };
+    }
+
+    /// Reads a register at `offset` bytes from the base of the mapping.
+    ///
+    /// # Safety
+    ///
+    /// `offset` must be within the mapped region and suitably aligned for `R`.
+    pub unsafe fn read_reg<R: Copy>(&self, offset: usize) -> R {
+        let addr = (self.ptr as *mut u8).wrapping_add(offset) as *mut R;
+        // SAFETY: caller guarantees `offset` lies within the mapping and is aligned for `R`.
+        unsafe { core::ptr::read_volatile(addr) }
+    }
+
+    /// Writes `val` to a register at `offset` bytes from the base of the mapping.
+    ///
+    /// # Safety
+    ///
+    /// `offset` must be within the mapped region and suitably aligned for `R`.
+    pub unsafe fn write_reg<R>(&self, offset: usize, val: R) {
+        let addr = (self.ptr as *mut u8).wrapping_add(offset) as *mut R;
+        // SAFETY: caller guarantees `offset` lies within the mapping and is aligned for `R`.
+        unsafe { core::ptr::write_volatile(addr, val) };
+    }
 }
 
 impl<T: Sized> Drop for IoReMapBox<T> {
@@ -27,10 +74,42 @@ impl<T: Sized> Drop for IoReMapBox<T> {
     }
 }
 
-impl<T: Sized> core::ops::Deref for IoReMapBox<T> {
-    type Target = T;
+/// A named, width-typed MMIO register accessor, addressed by byte offset from a mapping's base.
+///
+/// Reads and writes always go through `read_volatile`/`write_volatile`, so a PAC-style layer can
+/// address e.g. the PL011's FR/CR/IBRD/FBRD registers by name with guaranteed ordering instead of
+/// dereferencing a raw `*mut T`.
+pub struct Register<R> {
+    offset: usize,
+    _marker: core::marker::PhantomData<R>,
+}
+
+impl<R: Copy> Register<R> {
+    /// Creates a register accessor for the given byte `offset`.
+    pub const fn new(offset: usize) -> Self {
+        Self {
+            offset,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Reads this register out of `map`.
+    ///
+    /// # Safety
+    ///
+    /// `self.offset` must be within `map`'s mapped region and suitably aligned for `R`.
+    pub unsafe fn read<T: Sized>(&self, map: &IoReMapBox<T>) -> R {
+        // SAFETY: caller guarantees the offset is valid for `map`.
+        unsafe { map.read_reg(self.offset) }
+    }
 
-    fn deref(&self) -> &Self::Target {
-        unsafe { &*self.ptr }
+    /// Writes `val` into this register in `map`.
+    ///
+    /// # Safety
+    ///
+    /// `self.offset` must be within `map`'s mapped region and suitably aligned for `R`.
+    pub unsafe fn write<T: Sized>(&self, map: &IoReMapBox<T>, val: R) {
+        // SAFETY: caller guarantees the offset is valid for `map`.
+        unsafe { map.write_reg(self.offset, val) };
     }
 }
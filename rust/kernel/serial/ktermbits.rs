@@ -15,4 +15,200 @@ impl Ktermios {
     pub fn as_ptr(&self) -> *mut bindings::ktermios {
         self.0.get()
     }
+
+    fn c_cflag(&self) -> u32 {
+        unsafe { (*self.as_ptr()).c_cflag }
+    }
+
+    fn set_c_cflag(&mut self, cflag: u32) {
+        unsafe { (*self.as_ptr()).c_cflag = cflag };
+    }
+
+    /// Decode the standard termios `c_cflag`/`c_ospeed` bits into a typed [`Config`].
+    pub fn decode(&self) -> Config {
+        let cflag = self.c_cflag();
+
+        let data_bits = match cflag & bindings::CSIZE {
+            bindings::CS5 => DataBits::Five,
+            bindings::CS6 => DataBits::Six,
+            bindings::CS7 => DataBits::Seven,
+            _ => DataBits::Eight,
+        };
+
+        let parity = if cflag & bindings::PARENB == 0 {
+            Parity::None
+        } else if cflag & bindings::PARODD != 0 {
+            Parity::Odd
+        } else {
+            Parity::Even
+        };
+
+        let stop_bits = if cflag & bindings::CSTOPB != 0 {
+            StopBits::Two
+        } else {
+            StopBits::One
+        };
+
+        let rts_cts = cflag & bindings::CRTSCTS != 0;
+
+        // Prefer the split in/out speed fields populated by the tty layer; fall back to
+        // the kernel's own decoder if they haven't been filled in yet.
+        let baud_rate = unsafe {
+            let termios = &*self.as_ptr();
+            if termios.c_ospeed != 0 {
+                termios.c_ospeed
+            } else if termios.c_ispeed != 0 {
+                termios.c_ispeed
+            } else {
+                bindings::tty_termios_baud_rate(self.as_ptr())
+            }
+        };
+
+        Config {
+            data_bits,
+            parity,
+            stop_bits,
+            baud_rate,
+            rts_cts,
+        }
+    }
+
+    /// Inverse of [`Ktermios::decode`]: write a [`Config`] back into `c_cflag`.
+    pub fn apply(&mut self, config: &Config) {
+        let mut cflag = self.c_cflag()
+            & !(bindings::CSIZE | bindings::PARENB | bindings::PARODD | bindings::CRTSCTS);
+
+        cflag |= match config.data_bits {
+            DataBits::Five => bindings::CS5,
+            DataBits::Six => bindings::CS6,
+            DataBits::Seven => bindings::CS7,
+            DataBits::Eight => bindings::CS8,
+        };
+
+        cflag &= !bindings::CSTOPB;
+        if config.stop_bits == StopBits::Two {
+            cflag |= bindings::CSTOPB;
+        }
+
+        match config.parity {
+            Parity::None => {}
+            Parity::Even => cflag |= bindings::PARENB,
+            Parity::Odd => cflag |= bindings::PARENB | bindings::PARODD,
+        }
+
+        if config.rts_cts {
+            cflag |= bindings::CRTSCTS;
+        }
+
+        self.set_c_cflag(cflag);
+
+        unsafe {
+            let termios = &mut *self.as_ptr();
+            termios.c_ospeed = config.baud_rate;
+            termios.c_ispeed = config.baud_rate;
+        }
+    }
+}
+
+/// Number of data bits per character, decoded from `CSIZE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataBits {
+    Five,
+    Six,
+    Seven,
+    Eight,
+}
+
+/// Parity mode, decoded from `PARENB`/`PARODD`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parity {
+    None,
+    Even,
+    Odd,
+}
+
+/// Number of stop bits, decoded from `CSTOPB`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopBits {
+    One,
+    Two,
+}
+
+/// A line configuration decoded from (or to be applied to) a [`Ktermios`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    pub data_bits: DataBits,
+    pub parity: Parity,
+    pub stop_bits: StopBits,
+    pub baud_rate: u32,
+    /// Whether hardware (RTS/CTS) flow control is requested, decoded from `CRTSCTS`.
+    pub rts_cts: bool,
+}
+
+impl Config {
+    /// Parses a `console=`/`earlycon=` style options string, e.g. `b"115200n8"`
+    /// (`<baud>[<parity><data_bits>[<stop_bits>]]`), as documented in
+    /// `Documentation/admin-guide/kernel-parameters.txt`.
+    ///
+    /// Missing fields default to no parity, 8 data bits and 1 stop bit.
+    pub fn parse_earlycon_str(options: &[u8]) -> Option<Config> {
+        let options = match options.iter().position(|&b| b == 0) {
+            Some(end) => &options[..end],
+            None => options,
+        };
+
+        let digits_end = options
+            .iter()
+            .position(|b| !b.is_ascii_digit())
+            .unwrap_or(options.len());
+        if digits_end == 0 {
+            return None;
+        }
+        let baud_rate = core::str::from_utf8(&options[..digits_end])
+            .ok()?
+            .parse()
+            .ok()?;
+
+        let mut config = Config {
+            data_bits: DataBits::Eight,
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+            baud_rate,
+            rts_cts: false,
+        };
+
+        let rest = &options[digits_end..];
+        if let Some(&parity) = rest.first() {
+            config.parity = match parity {
+                b'n' => Parity::None,
+                b'e' => Parity::Even,
+                b'o' => Parity::Odd,
+                _ => return Some(config),
+            };
+        } else {
+            return Some(config);
+        }
+
+        if let Some(&data_bits) = rest.get(1) {
+            config.data_bits = match data_bits {
+                b'5' => DataBits::Five,
+                b'6' => DataBits::Six,
+                b'7' => DataBits::Seven,
+                b'8' => DataBits::Eight,
+                _ => return Some(config),
+            };
+        } else {
+            return Some(config);
+        }
+
+        if let Some(&stop_bits) = rest.get(2) {
+            config.stop_bits = match stop_bits {
+                b'1' => StopBits::One,
+                b'2' => StopBits::Two,
+                _ => return Some(config),
+            };
+        }
+
+        Some(config)
+    }
 }
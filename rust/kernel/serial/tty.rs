@@ -1,5 +1,28 @@
 use crate::types::Opaque;
 
+/// Per-character error flags passed to `uart_insert_char`, mirroring `TTY_NORMAL`/`TTY_BREAK`/
+/// `TTY_FRAME`/`TTY_PARITY`/`TTY_OVERRUN`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TtyFlag {
+    Normal,
+    Break,
+    Frame,
+    Parity,
+    Overrun,
+}
+
+impl TtyFlag {
+    pub(crate) fn as_raw(self) -> u32 {
+        match self {
+            TtyFlag::Normal => bindings::TTY_NORMAL,
+            TtyFlag::Break => bindings::TTY_BREAK,
+            TtyFlag::Frame => bindings::TTY_FRAME,
+            TtyFlag::Parity => bindings::TTY_PARITY,
+            TtyFlag::Overrun => bindings::TTY_OVERRUN,
+        }
+    }
+}
+
 pub struct SerialStruct(Opaque<bindings::serial_struct>);
 
 impl SerialStruct {
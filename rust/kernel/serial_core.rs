@@ -1,3 +1,396 @@
+/// A lock-free single-producer/single-consumer ring buffer, used to decouple interrupt-context
+/// byte pushes from task-context reads on the TX/RX side of a [`uart_port::UartPort`].
+pub mod ring_buffer {
+    use core::marker::PhantomData;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A fixed-capacity SPSC byte ring buffer over a backing slice installed with [`Self::init`].
+    ///
+    /// `start`/`end` are free-running indices into `buf`, taken modulo the buffer's length on
+    /// access. One slot is always kept empty so `start == end` unambiguously means empty and
+    /// `end - start >= usable_capacity()` (in the same free-running, unwrapped counter space)
+    /// unambiguously means full, letting a single producer and a single consumer advance their
+    /// own index with nothing but `Acquire`/`Release` atomics, no lock.
+    pub struct RingBuffer<'a> {
+        buf: *mut u8,
+        len: usize,
+        start: AtomicUsize,
+        end: AtomicUsize,
+        _marker: PhantomData<&'a mut [u8]>,
+    }
+
+    // SAFETY: `buf`/`len` are only ever installed by `init` (exclusive access) and read/written
+    // through `start`/`end`, which a single producer and a single consumer advance independently
+    // using `Acquire`/`Release` ordering; that is the whole SPSC contract this type provides.
+    unsafe impl<'a> Sync for RingBuffer<'a> {}
+
+    impl<'a> RingBuffer<'a> {
+        /// An uninitialized, zero-capacity ring buffer, usable as a `const` field initializer in
+        /// a driver's pinned state. Call [`Self::init`] before using it.
+        pub const fn new() -> Self {
+            Self {
+                buf: core::ptr::null_mut(),
+                len: 0,
+                start: AtomicUsize::new(0),
+                end: AtomicUsize::new(0),
+                _marker: PhantomData,
+            }
+        }
+
+        /// Installs `buf` (or its first `len` bytes, whichever is shorter) as the backing storage
+        /// and resets the buffer to empty.
+        pub fn init(&mut self, buf: &'a mut [u8], len: usize) {
+            self.len = len.min(buf.len());
+            self.buf = buf.as_mut_ptr();
+            self.start.store(0, Ordering::Release);
+            self.end.store(0, Ordering::Release);
+        }
+
+        /// Forgets the backing storage installed by [`Self::init`], returning the buffer to its
+        /// zero-capacity [`Self::new`] state.
+        pub fn deinit(&mut self) {
+            self.buf = core::ptr::null_mut();
+            self.len = 0;
+            self.start.store(0, Ordering::Release);
+            self.end.store(0, Ordering::Release);
+        }
+
+        fn capacity(&self) -> usize {
+            self.len
+        }
+
+        /// Usable capacity: one slot short of [`Self::capacity`], kept empty so the full/empty
+        /// boundary never has to be broken by tracking a separate count.
+        fn usable_capacity(&self) -> usize {
+            self.capacity().saturating_sub(1)
+        }
+
+        fn wrap(&self, idx: usize) -> usize {
+            if self.len == 0 {
+                0
+            } else {
+                idx % self.len
+            }
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.start.load(Ordering::Acquire) == self.end.load(Ordering::Acquire)
+        }
+
+        pub fn is_full(&self) -> bool {
+            let start = self.start.load(Ordering::Acquire);
+            let end = self.end.load(Ordering::Acquire);
+            (end - start) >= self.usable_capacity()
+        }
+
+        /// Returns the largest contiguous filled span, i.e. up to the end of the backing array
+        /// before wraparound. Call [`Self::pop`] with however many bytes were actually consumed.
+        pub fn pop_buf(&self) -> &[u8] {
+            if self.len == 0 {
+                return &[];
+            }
+            let start = self.start.load(Ordering::Acquire);
+            let end = self.end.load(Ordering::Acquire);
+            let offset = self.wrap(start);
+            let span = (end - start).min(self.len - offset);
+            // SAFETY: `offset + span` never crosses `len`, and `span` is bounded by how many
+            // bytes the writer has published via `end`'s `Release` store, so this span has
+            // already been fully written.
+            unsafe { core::slice::from_raw_parts(self.buf.add(offset), span) }
+        }
+
+        /// Commits that `n` bytes returned by [`Self::pop_buf`] were consumed.
+        pub fn pop(&self, n: usize) {
+            self.start.fetch_add(n, Ordering::Release);
+        }
+
+        /// Returns the largest contiguous free span, i.e. up to the end of the backing array
+        /// before wraparound. Call [`Self::push`] with however many bytes were actually written.
+        pub fn push_buf(&self) -> &mut [u8] {
+            if self.len == 0 {
+                return &mut [];
+            }
+            let start = self.start.load(Ordering::Acquire);
+            let end = self.end.load(Ordering::Acquire);
+            let filled = (end - start).min(self.usable_capacity());
+            let free = self.usable_capacity() - filled;
+            let offset = self.wrap(end);
+            let span = free.min(self.len - offset);
+            // SAFETY: this span lies strictly between `end` and the reader's `start`, which the
+            // reader only ever advances forward, so the producer has exclusive access to it.
+            unsafe { core::slice::from_raw_parts_mut(self.buf.add(offset), span) }
+        }
+
+        /// Commits that `n` bytes returned by [`Self::push_buf`] were written.
+        pub fn push(&self, n: usize) {
+            self.end.fetch_add(n, Ordering::Release);
+        }
+
+        /// Copies as much of `data` as fits into the buffer, looping over [`Self::push_buf`] to
+        /// handle wraparound. Returns the number of bytes actually copied.
+        pub fn push_slice(&self, data: &[u8]) -> usize {
+            let mut written = 0;
+            while written < data.len() {
+                let buf = self.push_buf();
+                if buf.is_empty() {
+                    break;
+                }
+                let n = buf.len().min(data.len() - written);
+                buf[..n].copy_from_slice(&data[written..written + n]);
+                self.push(n);
+                written += n;
+            }
+            written
+        }
+
+        /// Copies as much as fits into `out` out of the buffer, looping over [`Self::pop_buf`] to
+        /// handle wraparound. Returns the number of bytes actually copied.
+        pub fn pop_slice(&self, out: &mut [u8]) -> usize {
+            let mut read = 0;
+            while read < out.len() {
+                let buf = self.pop_buf();
+                if buf.is_empty() {
+                    break;
+                }
+                let n = buf.len().min(out.len() - read);
+                out[read..read + n].copy_from_slice(&buf[..n]);
+                self.pop(n);
+                read += n;
+            }
+            read
+        }
+
+        /// A handle restricted to the consumer-side operations, movable into task context while
+        /// a [`Writer`] stays behind in interrupt context (or vice versa).
+        pub fn reader(&self) -> Reader<'a, '_> {
+            Reader { ring: self }
+        }
+
+        /// A handle restricted to the producer-side operations, movable into interrupt context
+        /// while a [`Reader`] stays behind in task context (or vice versa).
+        pub fn writer(&self) -> Writer<'a, '_> {
+            Writer { ring: self }
+        }
+    }
+
+    /// The consumer half of a [`RingBuffer`] split. See [`RingBuffer::reader`].
+    pub struct Reader<'a, 'b> {
+        ring: &'b RingBuffer<'a>,
+    }
+
+    impl<'a, 'b> Reader<'a, 'b> {
+        pub fn is_empty(&self) -> bool {
+            self.ring.is_empty()
+        }
+
+        pub fn pop_buf(&self) -> &[u8] {
+            self.ring.pop_buf()
+        }
+
+        pub fn pop(&self, n: usize) {
+            self.ring.pop(n)
+        }
+
+        pub fn pop_slice(&self, out: &mut [u8]) -> usize {
+            self.ring.pop_slice(out)
+        }
+    }
+
+    /// The producer half of a [`RingBuffer`] split. See [`RingBuffer::writer`].
+    pub struct Writer<'a, 'b> {
+        ring: &'b RingBuffer<'a>,
+    }
+
+    impl<'a, 'b> Writer<'a, 'b> {
+        pub fn is_full(&self) -> bool {
+            self.ring.is_full()
+        }
+
+        pub fn push_buf(&self) -> &mut [u8] {
+            self.ring.push_buf()
+        }
+
+        pub fn push(&self, n: usize) {
+            self.ring.push(n)
+        }
+
+        pub fn push_slice(&self, data: &[u8]) -> usize {
+            self.ring.push_slice(data)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn ring(storage: &mut [u8]) -> RingBuffer<'_> {
+            let len = storage.len();
+            let mut ring = RingBuffer::new();
+            ring.init(storage, len);
+            ring
+        }
+
+        #[test]
+        fn starts_empty_and_not_full() {
+            let mut storage = [0u8; 4];
+            let ring = ring(&mut storage);
+            assert!(ring.is_empty());
+            assert!(!ring.is_full());
+        }
+
+        #[test]
+        fn fills_to_one_short_of_capacity() {
+            let mut storage = [0u8; 4];
+            let ring = ring(&mut storage);
+            assert_eq!(ring.push_slice(&[1, 2, 3, 4]), 3);
+            assert!(ring.is_full());
+            assert!(!ring.is_empty());
+        }
+
+        #[test]
+        fn drains_back_to_empty() {
+            let mut storage = [0u8; 4];
+            let ring = ring(&mut storage);
+            ring.push_slice(&[1, 2, 3]);
+            let mut out = [0u8; 3];
+            assert_eq!(ring.pop_slice(&mut out), 3);
+            assert_eq!(out, [1, 2, 3]);
+            assert!(ring.is_empty());
+        }
+
+        #[test]
+        fn wraps_around_the_backing_array() {
+            let mut storage = [0u8; 4];
+            let ring = ring(&mut storage);
+            assert_eq!(ring.push_slice(&[1, 2, 3]), 3);
+            let mut out = [0u8; 2];
+            assert_eq!(ring.pop_slice(&mut out), 2);
+            assert_eq!(out, [1, 2]);
+            // start/end now sit at 2/3, near the end of the 4-byte backing array; pushing
+            // again forces push_buf/pop_buf to wrap.
+            assert_eq!(ring.push_slice(&[4, 5]), 2);
+            let mut out = [0u8; 3];
+            assert_eq!(ring.pop_slice(&mut out), 3);
+            assert_eq!(out, [3, 4, 5]);
+            assert!(ring.is_empty());
+        }
+
+        #[test]
+        fn is_full_and_is_empty_after_wraparound() {
+            let mut storage = [0u8; 4];
+            let ring = ring(&mut storage);
+            // Advance start/end past the end of the 4-byte backing array at least once before
+            // checking is_full/is_empty, so a wrap()-based comparison that only works while
+            // start == 0 would be caught.
+            assert_eq!(ring.push_slice(&[1, 2, 3]), 3);
+            let mut out = [0u8; 2];
+            assert_eq!(ring.pop_slice(&mut out), 2);
+            assert_eq!(ring.push_slice(&[4, 5, 6]), 2);
+            assert!(ring.is_full());
+            assert!(!ring.is_empty());
+            let mut out = [0u8; 3];
+            assert_eq!(ring.pop_slice(&mut out), 3);
+            assert!(ring.is_empty());
+            assert!(!ring.is_full());
+        }
+
+        #[test]
+        fn push_slice_stops_when_full() {
+            let mut storage = [0u8; 4];
+            let ring = ring(&mut storage);
+            assert_eq!(ring.push_slice(&[1, 2, 3, 4, 5]), 3);
+            assert!(ring.is_full());
+        }
+
+        #[test]
+        fn pop_slice_stops_when_empty() {
+            let mut storage = [0u8; 4];
+            let ring = ring(&mut storage);
+            ring.push_slice(&[1]);
+            let mut out = [0u8; 4];
+            assert_eq!(ring.pop_slice(&mut out), 1);
+            assert_eq!(ring.pop_slice(&mut out), 0);
+        }
+
+        #[test]
+        fn deinit_resets_to_zero_capacity() {
+            let mut storage = [0u8; 4];
+            let mut ring = ring(&mut storage);
+            ring.push_slice(&[1, 2]);
+            ring.deinit();
+            assert_eq!(ring.push_slice(&[1]), 0);
+            assert_eq!(ring.pop_slice(&mut [0u8; 1]), 0);
+        }
+    }
+}
+
+/// A safe wrapper around `ktermios`, mirroring the parsing `serial_core.c` does at the top of
+/// every driver's `set_termios`.
+///
+/// The decoded line-configuration types ([`Config`] and friends) live in
+/// [`crate::serial::ktermbits`] and are shared with [`crate::serial::ktermbits::Ktermios`], the
+/// other `ktermios` wrapper, rather than duplicated here.
+pub mod ktermbits {
+    use crate::serial::ktermbits::{Config, DataBits, Parity, StopBits};
+    use crate::types::Opaque;
+
+    /// A `ktermios` reference whose `c_cflag` bits can be decoded without touching FFI.
+    pub struct Termios(Opaque<bindings::ktermios>);
+
+    impl Termios {
+        pub fn from_raw<'a>(ptr: *mut bindings::ktermios) -> &'a mut Self {
+            unsafe { &mut *ptr.cast() }
+        }
+
+        pub fn from_raw_const<'a>(ptr: *const bindings::ktermios) -> &'a Self {
+            unsafe { &*ptr.cast() }
+        }
+
+        pub fn as_ptr(&self) -> *mut bindings::ktermios {
+            self.0.get()
+        }
+
+        fn c_cflag(&self) -> u32 {
+            unsafe { (*self.as_ptr()).c_cflag }
+        }
+
+        /// Decodes `c_cflag` into a [`Config`], the same fields `serial_core.c` pulls out before
+        /// calling into a driver's `set_termios`.
+        pub fn decode(&self) -> Config {
+            let cflag = self.c_cflag();
+
+            let data_bits = match cflag & bindings::CSIZE {
+                bindings::CS5 => DataBits::Five,
+                bindings::CS6 => DataBits::Six,
+                bindings::CS7 => DataBits::Seven,
+                _ => DataBits::Eight,
+            };
+
+            let parity = if cflag & bindings::PARENB == 0 {
+                Parity::None
+            } else if cflag & bindings::PARODD != 0 {
+                Parity::Odd
+            } else {
+                Parity::Even
+            };
+
+            let stop_bits = if cflag & bindings::CSTOPB != 0 {
+                StopBits::Two
+            } else {
+                StopBits::One
+            };
+
+            Config {
+                data_bits,
+                parity,
+                stop_bits,
+                baud_rate: unsafe { bindings::tty_termios_baud_rate(self.as_ptr()) },
+                rts_cts: cflag & bindings::CRTSCTS != 0,
+            }
+        }
+    }
+}
+
 pub mod uart_port {
     use crate::{console, device::Device};
     use core::marker;
@@ -5,6 +398,96 @@ pub mod uart_port {
 
     use crate::{container_of, types::Opaque};
     use bindings::{serial_struct, uart_ops, uart_port};
+    use crate::serial::tty::TtyFlag;
+    use crate::serial_core::ktermbits::Termios;
+    use crate::serial_core::ring_buffer::RingBuffer;
+    use macros::vtable;
+
+    /// Software TX/RX buffering layered on top of a [`UartPort`], decoupling interrupt-context
+    /// byte pushes from task-context reads via a pair of lock-free [`RingBuffer`]s.
+    pub struct BufferedUart<'a> {
+        pub tx: RingBuffer<'a>,
+        pub rx: RingBuffer<'a>,
+    }
+
+    impl<'a> BufferedUart<'a> {
+        pub fn new(tx_buf: &'a mut [u8], rx_buf: &'a mut [u8]) -> Self {
+            let mut tx = RingBuffer::new();
+            let tx_len = tx_buf.len();
+            tx.init(tx_buf, tx_len);
+
+            let mut rx = RingBuffer::new();
+            let rx_len = rx_buf.len();
+            rx.init(rx_buf, rx_len);
+
+            Self { tx, rx }
+        }
+    }
+
+    /// A safe wrapper around `serial_rs485`: the enable flag, RTS-on-send/after-send polarity,
+    /// and the millisecond RTS settle delays a half-duplex transceiver driver needs.
+    pub struct SerialRs485(Opaque<bindings::serial_rs485>);
+
+    impl SerialRs485 {
+        pub fn from_raw<'a>(ptr: *mut bindings::serial_rs485) -> &'a mut Self {
+            unsafe { &mut *ptr.cast() }
+        }
+
+        pub fn as_ptr(&self) -> *mut bindings::serial_rs485 {
+            self.0.get()
+        }
+
+        fn set_flag(&mut self, flag: u32, on: bool) {
+            unsafe {
+                let ptr = self.as_ptr();
+                if on {
+                    (*ptr).flags |= flag;
+                } else {
+                    (*ptr).flags &= !flag;
+                }
+            }
+        }
+
+        pub fn enabled(&self) -> bool {
+            unsafe { (*self.as_ptr()).flags & bindings::SER_RS485_ENABLED != 0 }
+        }
+
+        pub fn set_enabled(&mut self, enabled: bool) {
+            self.set_flag(bindings::SER_RS485_ENABLED, enabled);
+        }
+
+        pub fn rts_on_send(&self) -> bool {
+            unsafe { (*self.as_ptr()).flags & bindings::SER_RS485_RTS_ON_SEND != 0 }
+        }
+
+        pub fn set_rts_on_send(&mut self, on: bool) {
+            self.set_flag(bindings::SER_RS485_RTS_ON_SEND, on);
+        }
+
+        pub fn rts_after_send(&self) -> bool {
+            unsafe { (*self.as_ptr()).flags & bindings::SER_RS485_RTS_AFTER_SEND != 0 }
+        }
+
+        pub fn set_rts_after_send(&mut self, on: bool) {
+            self.set_flag(bindings::SER_RS485_RTS_AFTER_SEND, on);
+        }
+
+        pub fn delay_rts_before_send(&self) -> u32 {
+            unsafe { (*self.as_ptr()).delay_rts_before_send }
+        }
+
+        pub fn set_delay_rts_before_send(&mut self, delay_ms: u32) {
+            unsafe { (*self.as_ptr()).delay_rts_before_send = delay_ms };
+        }
+
+        pub fn delay_rts_after_send(&self) -> u32 {
+            unsafe { (*self.as_ptr()).delay_rts_after_send }
+        }
+
+        pub fn set_delay_rts_after_send(&mut self, delay_ms: u32) {
+            unsafe { (*self.as_ptr()).delay_rts_after_send = delay_ms };
+        }
+    }
 
     pub unsafe trait RawUartPort {
         fn raw_uart_port(&self) -> *mut uart_port;
@@ -15,7 +498,7 @@ pub mod uart_port {
 
     unsafe impl RawUartPort for UartPort {
         fn raw_uart_port(&self) -> *mut uart_port {
-            &self as *const _ as *mut uart_port
+            self.0.get()
         }
     }
 
@@ -24,11 +507,122 @@ pub mod uart_port {
             unsafe { &mut *ptr.cast() }
         }
 
-        pub fn new<T: UartOps>(ops: &T) -> Self {
+        pub fn new<T: UartOps>() -> Self {
             let mut uart_port = bindings::uart_port::default();
             uart_port.ops = unsafe { OperationsVtable::<T>::build() };
             Self(Opaque::new(uart_port))
         }
+
+        /// Wraps `uart_get_baud_rate`: clamps the rate requested in `termios` to `[min, max]`.
+        /// No old termios is passed, so an out-of-range request is clipped to just inside that
+        /// range rather than falling back to the port's previous speed.
+        pub fn get_baud_rate(&self, termios: &Termios, min: u32, max: u32) -> u32 {
+            unsafe {
+                bindings::uart_get_baud_rate(
+                    self.raw_uart_port(),
+                    termios.as_ptr(),
+                    core::ptr::null_mut(),
+                    min,
+                    max,
+                )
+            }
+        }
+
+        /// Recomputes `port->timeout`, the same way `uart_update_timeout` does: the wall-clock
+        /// time (in jiffies) to drain one FIFO's worth of `bits_per_char`-sized frames at `baud`,
+        /// plus a small fixed slop.
+        pub fn update_timeout(&mut self, baud: u32, bits_per_char: u32) {
+            let port = unsafe { &mut *self.raw_uart_port() };
+            let bits = bits_per_char.saturating_mul(port.fifosize);
+            port.timeout = (bits * bindings::HZ).div_ceil(baud.max(1)) + bindings::HZ / 50;
+        }
+
+        /// Pushes one received byte into the tty flip buffer, the same way `uart_insert_char`
+        /// does: `ch` is queued tagged with `flag`, and if `overrun` is set a `TTY_OVERRUN`
+        /// marker is queued after it. Does not make the byte visible to the line discipline by
+        /// itself; call [`UartPort::flip_buffer_push`] once the FIFO has been drained.
+        pub fn insert_char(&mut self, ch: u8, overrun: bool, flag: TtyFlag) {
+            let port = self.raw_uart_port();
+            unsafe {
+                bindings::uart_insert_char(
+                    port,
+                    overrun as core::ffi::c_uint,
+                    1,
+                    ch as core::ffi::c_uint,
+                    flag.as_raw(),
+                );
+            }
+        }
+
+        /// Wraps `tty_flip_buffer_push`: hands every byte queued by [`UartPort::insert_char`]
+        /// since the last call to the line discipline.
+        pub fn flip_buffer_push(&mut self) {
+            let port = unsafe { &mut *self.raw_uart_port() };
+            unsafe { bindings::tty_flip_buffer_push(&mut (*port.state).port) };
+        }
+
+        /// Walks the port's circular xmit buffer (`port->state->xmit`), feeding pending bytes to
+        /// `f` one at a time and advancing `tail` past each byte `f` accepts. Stops when the
+        /// buffer runs dry or `f` returns `false` (e.g. because the hardware FIFO is full).
+        pub fn for_each_pending_tx(&mut self, mut f: impl FnMut(u8) -> bool) {
+            let port = unsafe { &mut *self.raw_uart_port() };
+            let state = unsafe { &mut *port.state };
+            let xmit = &mut state.xmit;
+            let mask = (bindings::UART_XMIT_SIZE - 1) as i32;
+
+            while xmit.head != xmit.tail {
+                let ch = xmit.buf[xmit.tail as usize] as u8;
+                if !f(ch) {
+                    break;
+                }
+                xmit.tail = (xmit.tail + 1) & mask;
+                port.icount.tx += 1;
+            }
+        }
+
+        /// Wraps `uart_write_wakeup`: tells the line discipline more room is available in the
+        /// xmit buffer, once it has drained below `WAKEUP_CHARS`.
+        pub fn write_wakeup(&mut self) {
+            let port = self.raw_uart_port();
+            let xmit = unsafe { &(*(*port).state).xmit };
+            let pending = (xmit.head - xmit.tail) & (bindings::UART_XMIT_SIZE - 1) as i32;
+            if pending < bindings::WAKEUP_CHARS as i32 {
+                unsafe { bindings::uart_write_wakeup(port) };
+            }
+        }
+
+        /// Number of bytes received so far (`port->icount.rx`).
+        pub fn icount_rx(&self) -> u32 {
+            unsafe { (*self.raw_uart_port()).icount.rx }
+        }
+
+        /// Number of bytes transmitted so far (`port->icount.tx`).
+        pub fn icount_tx(&self) -> u32 {
+            unsafe { (*self.raw_uart_port()).icount.tx }
+        }
+
+        /// Number of receive FIFO overruns seen so far (`port->icount.overrun`).
+        pub fn icount_overrun(&self) -> u32 {
+            unsafe { (*self.raw_uart_port()).icount.overrun }
+        }
+
+        /// Number of framing errors seen so far (`port->icount.frame`).
+        pub fn icount_frame(&self) -> u32 {
+            unsafe { (*self.raw_uart_port()).icount.frame }
+        }
+
+        /// Number of parity errors seen so far (`port->icount.parity`).
+        pub fn icount_parity(&self) -> u32 {
+            unsafe { (*self.raw_uart_port()).icount.parity }
+        }
+    }
+
+    /// `DIV_ROUND_CLOSEST(clk, 16 * baud)`, i.e. the standard UART clock divisor, clamped to
+    /// `max_divisor` (hardware dividers are always some fixed width).
+    pub fn compute_divisor(baud: u32, clk: u64, max_divisor: u32) -> u32 {
+        let baud = (baud.max(1) as u64).max(1);
+        let divisor = (clk + 8 * baud) / (16 * baud);
+        (divisor as u32).min(max_divisor)
     }
 
     /// UART operations vtable
@@ -55,40 +649,66 @@ pub mod uart_port {
     /// * @request_port: request the UART port
     /// * @config_port:  configure the UART port
     /// * @verify_port:  verify the UART port
-    /// * @ioctl:        ioctl handler
+    /// * @ioctl:        ioctl handler (Optional)
+    /// * @rs485_config: configure RS485 half-duplex mode (Optional)
+    ///
+    /// Every method has a default, no-op implementation, so a driver only needs to implement the
+    /// callbacks its hardware actually uses; [`OperationsVtable`] leaves the rest null in the
+    /// `uart_ops` it builds, just like mainline C drivers only populate the fields they need.
+    #[vtable]
     pub trait UartOps {
-        fn tx_empty(uart_port: &mut UartPort) -> u32;
-        fn set_mctrl(uart_port: &mut UartPort, mctrl: u32);
-        fn get_mctrl(uart_port: &mut UartPort) -> u32;
-        fn stop_tx(uart_port: &mut UartPort);
-        fn start_tx(uart_port: &mut UartPort);
-        fn throttle(uart_port: &mut UartPort);
-        fn unthrottle(uart_port: &mut UartPort);
-        fn send_xchar(uart_port: &mut UartPort, ch: i8);
-        fn stop_rx(uart_port: &mut UartPort);
-        fn start_rx(uart_port: &mut UartPort);
-        fn enable_ms(uart_port: &mut UartPort);
-        fn break_ctl(uart_port: &mut UartPort, ctl: i32);
-        fn startup(uart_port: &mut UartPort) -> i32;
-        fn shutdown(uart_port: &mut UartPort);
-        fn flush_buffer(uart_port: &mut UartPort);
-        fn set_termios(
-            uart_port: &mut UartPort,
-            new: *mut bindings::ktermios,
-            old: *const bindings::ktermios,
-        );
-        fn set_ldisc(uart_port: &mut UartPort, arg2: *mut bindings::ktermios);
-        fn pm(uart_port: &mut UartPort, state: u32, oldstate: u32);
-        fn type_(uart_port: &mut UartPort) -> *const i8;
-        fn release_port(uart_port: &mut UartPort);
-        fn request_port(uart_port: &mut UartPort) -> i32;
-        fn config_port(uart_port: &mut UartPort, arg2: i32);
-        fn verify_port(uart_port: &mut UartPort, arg2: *mut serial_struct) -> i32;
-        fn ioctl(uart_port: &mut UartPort, arg2: u32, arg3: u64) -> i32;
-
-        fn poll_init(uart_port: &mut UartPort) -> i32;
-        fn poll_put_char(uart_port: &mut UartPort, arg2: u8);
-        fn poll_get_char(uart_port: &mut UartPort) -> i32;
+        fn tx_empty(_uart_port: &mut UartPort) -> u32 {
+            0
+        }
+        fn set_mctrl(_uart_port: &mut UartPort, _mctrl: u32) {}
+        fn get_mctrl(_uart_port: &mut UartPort) -> u32 {
+            0
+        }
+        fn stop_tx(_uart_port: &mut UartPort) {}
+        fn start_tx(_uart_port: &mut UartPort) {}
+        fn throttle(_uart_port: &mut UartPort) {}
+        fn unthrottle(_uart_port: &mut UartPort) {}
+        fn send_xchar(_uart_port: &mut UartPort, _ch: i8) {}
+        fn stop_rx(_uart_port: &mut UartPort) {}
+        fn start_rx(_uart_port: &mut UartPort) {}
+        fn enable_ms(_uart_port: &mut UartPort) {}
+        fn break_ctl(_uart_port: &mut UartPort, _ctl: i32) {}
+        fn startup(_uart_port: &mut UartPort) -> i32 {
+            0
+        }
+        fn shutdown(_uart_port: &mut UartPort) {}
+        fn flush_buffer(_uart_port: &mut UartPort) {}
+        fn set_termios(_uart_port: &mut UartPort, _new: &mut Termios, _old: &Termios) {}
+        fn set_ldisc(_uart_port: &mut UartPort, _arg2: *mut bindings::ktermios) {}
+        fn pm(_uart_port: &mut UartPort, _state: u32, _oldstate: u32) {}
+        fn type_(_uart_port: &mut UartPort) -> *const i8 {
+            core::ptr::null()
+        }
+        fn release_port(_uart_port: &mut UartPort) {}
+        fn request_port(_uart_port: &mut UartPort) -> i32 {
+            0
+        }
+        fn config_port(_uart_port: &mut UartPort, _arg2: i32) {}
+        fn verify_port(_uart_port: &mut UartPort, _arg2: *mut serial_struct) -> i32 {
+            0
+        }
+        fn ioctl(_uart_port: &mut UartPort, _arg2: u32, _arg3: u64) -> i32 {
+            -1
+        }
+
+        fn poll_init(_uart_port: &mut UartPort) -> i32 {
+            0
+        }
+        fn poll_put_char(_uart_port: &mut UartPort, _arg2: u8) {}
+        fn poll_get_char(_uart_port: &mut UartPort) -> i32 {
+            -1
+        }
+
+        /// Enables or disables RS485 half-duplex mode and programs the driver-visible RTS
+        /// timing fields in `rs485`. Only called when the driver overrides this method.
+        fn rs485_config(_uart_port: &mut UartPort, _rs485: &mut SerialRs485) -> Result {
+            Err(error::code::EOPNOTSUPP)
+        }
     }
 
     pub(crate) struct OperationsVtable<T>(marker::PhantomData<T>);
@@ -175,6 +795,8 @@ pub mod uart_port {
             old: *const bindings::ktermios,
         ) {
             let uart_port = UartPort::from_ptr(uart_port);
+            let new = Termios::from_raw(new);
+            let old = Termios::from_raw_const(old);
             T::set_termios(uart_port, new, old)
         }
 
@@ -244,40 +866,93 @@ pub mod uart_port {
             T::poll_get_char(uart_port)
         }
 
+        unsafe extern "C" fn rs485_config(
+            uart_port: *mut uart_port,
+            _termios: *mut bindings::ktermios,
+            rs485: *mut bindings::serial_rs485,
+        ) -> core::ffi::c_int {
+            let uart_port = UartPort::from_ptr(uart_port);
+            let rs485 = SerialRs485::from_raw(rs485);
+            match T::rs485_config(uart_port, rs485) {
+                Ok(()) => 0,
+                Err(e) => e.to_errno(),
+            }
+        }
+
         const VTABLE: bindings::uart_ops = bindings::uart_ops {
-            tx_empty: Some(Self::tx_empty),
-            set_mctrl: Some(Self::set_mctrl),
-            get_mctrl: Some(Self::get_mctrl),
-            stop_tx: Some(Self::stop_tx),
-            start_tx: Some(Self::start_tx),
-            throttle: Some(Self::throttle),
-            unthrottle: Some(Self::unthrottle),
-            send_xchar: Some(Self::send_xchar),
-            stop_rx: Some(Self::stop_rx),
-            start_rx: Some(Self::start_rx),
-            enable_ms: Some(Self::enable_ms),
-            break_ctl: Some(Self::break_ctl),
-            startup: Some(Self::startup),
-            shutdown: Some(Self::shutdown),
-            flush_buffer: Some(Self::flush_buffer),
-            set_termios: Some(Self::set_termios),
-            set_ldisc: Some(Self::set_ldisc),
-            pm: Some(Self::pm),
-            type_: Some(Self::type_),
-            release_port: Some(Self::release_port),
-            request_port: Some(Self::request_port),
-            config_port: Some(Self::config_port),
-            verify_port: Some(Self::verify_port),
-            ioctl: Some(Self::ioctl),
-            poll_init: Some(Self::poll_init),
-            poll_put_char: Some(Self::poll_put_char),
-            poll_get_char: Some(Self::poll_get_char),
+            tx_empty: if T::HAS_TX_EMPTY { Some(Self::tx_empty) } else { None },
+            set_mctrl: if T::HAS_SET_MCTRL { Some(Self::set_mctrl) } else { None },
+            get_mctrl: if T::HAS_GET_MCTRL { Some(Self::get_mctrl) } else { None },
+            stop_tx: if T::HAS_STOP_TX { Some(Self::stop_tx) } else { None },
+            start_tx: if T::HAS_START_TX { Some(Self::start_tx) } else { None },
+            throttle: if T::HAS_THROTTLE { Some(Self::throttle) } else { None },
+            unthrottle: if T::HAS_UNTHROTTLE { Some(Self::unthrottle) } else { None },
+            send_xchar: if T::HAS_SEND_XCHAR { Some(Self::send_xchar) } else { None },
+            stop_rx: if T::HAS_STOP_RX { Some(Self::stop_rx) } else { None },
+            start_rx: if T::HAS_START_RX { Some(Self::start_rx) } else { None },
+            enable_ms: if T::HAS_ENABLE_MS { Some(Self::enable_ms) } else { None },
+            break_ctl: if T::HAS_BREAK_CTL { Some(Self::break_ctl) } else { None },
+            startup: if T::HAS_STARTUP { Some(Self::startup) } else { None },
+            shutdown: if T::HAS_SHUTDOWN { Some(Self::shutdown) } else { None },
+            flush_buffer: if T::HAS_FLUSH_BUFFER { Some(Self::flush_buffer) } else { None },
+            set_termios: if T::HAS_SET_TERMIOS { Some(Self::set_termios) } else { None },
+            set_ldisc: if T::HAS_SET_LDISC { Some(Self::set_ldisc) } else { None },
+            pm: if T::HAS_PM { Some(Self::pm) } else { None },
+            type_: if T::HAS_TYPE_ { Some(Self::type_) } else { None },
+            release_port: if T::HAS_RELEASE_PORT { Some(Self::release_port) } else { None },
+            request_port: if T::HAS_REQUEST_PORT { Some(Self::request_port) } else { None },
+            config_port: if T::HAS_CONFIG_PORT { Some(Self::config_port) } else { None },
+            verify_port: if T::HAS_VERIFY_PORT { Some(Self::verify_port) } else { None },
+            ioctl: if T::HAS_IOCTL { Some(Self::ioctl) } else { None },
+            poll_init: if T::HAS_POLL_INIT { Some(Self::poll_init) } else { None },
+            poll_put_char: if T::HAS_POLL_PUT_CHAR { Some(Self::poll_put_char) } else { None },
+            poll_get_char: if T::HAS_POLL_GET_CHAR { Some(Self::poll_get_char) } else { None },
+            rs485_config: if T::HAS_RS485_CONFIG {
+                Some(Self::rs485_config)
+            } else {
+                None
+            },
         };
 
         pub(crate) unsafe fn build() -> *const bindings::uart_ops {
             &Self::VTABLE as *const _
         }
     }
+
+    /// Blocking helpers built on the `poll_*` [`UartOps`] hooks, usable as an early console or a
+    /// kgdb I/O backend before interrupts are available.
+    ///
+    /// Blanket-implemented for every [`UartOps`]: spins on `tx_empty`/`poll_get_char` the same
+    /// way the polling console path does, rather than waiting on an interrupt.
+    pub trait PolledUart: UartOps {
+        /// Blocks until `tx_empty` reports the transmitter empty, then writes the byte.
+        fn poll_write_byte(uart_port: &mut UartPort, ch: u8) {
+            while Self::tx_empty(uart_port) == 0 {
+                core::hint::spin_loop();
+            }
+            Self::poll_put_char(uart_port, ch);
+        }
+
+        /// Blocks, writing every byte of `s` one at a time.
+        fn poll_write_str(uart_port: &mut UartPort, s: &[u8]) {
+            for &ch in s {
+                Self::poll_write_byte(uart_port, ch);
+            }
+        }
+
+        /// Blocks until the RX FIFO has a byte available, then returns it.
+        fn poll_read_byte(uart_port: &mut UartPort) -> u8 {
+            loop {
+                let ch = Self::poll_get_char(uart_port);
+                if ch >= 0 {
+                    return ch as u8;
+                }
+                core::hint::spin_loop();
+            }
+        }
+    }
+
+    impl<T: UartOps> PolledUart for T {}
 }
 
 pub mod uart_driver {
@@ -440,4 +1115,108 @@ pub mod uart_driver {
             }
         }
     }
+
+    /// A safe wrapper around `earlycon_device`: the bare `uart_port` plus the `earlycon=`/
+    /// device-tree options string an [`EarlyCon`] setup callback has to work with, before the
+    /// rest of the driver (interrupts, DMA, the tty layer) has come up.
+    pub struct EarlyConDevice(crate::types::Opaque<bindings::earlycon_device>);
+
+    impl EarlyConDevice {
+        pub fn from_raw<'a>(ptr: *mut bindings::earlycon_device) -> &'a mut Self {
+            unsafe { &mut *ptr.cast() }
+        }
+
+        pub fn as_ptr(&self) -> *mut bindings::earlycon_device {
+            self.0.get()
+        }
+
+        /// The `uart_port` embedded in this `earlycon_device`.
+        pub fn port(&mut self) -> &mut uart_port::UartPort {
+            uart_port::UartPort::from_ptr(unsafe { &mut (*self.as_ptr()).port })
+        }
+    }
+
+    /// A boot-time console backend, registered via [`of_earlycon_declare`] and driven by
+    /// `of_setup_earlycon` long before the matching [`Registration`] has probed.
+    pub trait EarlyCon {
+        /// Minimally programs the hardware (baud rate, line settings) so [`Self::write`] can run;
+        /// called once, from `of_setup_earlycon`, with the `earlycon=`/device-tree options string.
+        fn setup(device: &mut EarlyConDevice, options: Option<&crate::str::CStr>) -> Result;
+
+        /// Busy-polls the TX register to emit `s`, the same output path a console `write`
+        /// callback uses, but without relying on interrupts or the tty layer being up yet.
+        fn write(device: &mut EarlyConDevice, s: &str);
+    }
+
+    pub struct EarlyConVtable<T>(core::marker::PhantomData<T>);
+
+    impl<T: EarlyCon> EarlyConVtable<T> {
+        unsafe extern "C" fn write(
+            co: *mut bindings::console,
+            s: *const core::ffi::c_char,
+            count: core::ffi::c_uint,
+        ) {
+            let device = unsafe { (*co).data as *mut bindings::earlycon_device };
+            let device = EarlyConDevice::from_raw(device);
+            let s = unsafe { core::slice::from_raw_parts(s as *const u8, count as usize) };
+            if let Ok(s) = core::str::from_utf8(s) {
+                T::write(device, s);
+            }
+        }
+
+        /// The `earlycon_id.setup` entry point `of_setup_earlycon` calls: installs `T::write` as
+        /// the earlycon's `console.write` and then runs `T::setup`.
+        pub unsafe extern "C" fn probe(
+            device: *mut bindings::earlycon_device,
+            options: *const core::ffi::c_char,
+        ) -> core::ffi::c_int {
+            error::from_result(|| {
+                let con = unsafe { (*device).con };
+                if !con.is_null() {
+                    unsafe { (*con).write = Some(Self::write) };
+                    unsafe { (*con).data = device as *mut core::ffi::c_void };
+                }
+                let device = EarlyConDevice::from_raw(device);
+                let options = if options.is_null() {
+                    None
+                } else {
+                    Some(unsafe { crate::str::CStr::from_char_ptr(options) })
+                };
+                T::setup(device, options)
+            })
+        }
+    }
+}
+
+/// Equivalent of the C `OF_EARLYCON_DECLARE(name, compat, fn)` macro: registers `$ty`'s
+/// [`uart_driver::EarlyCon`] implementation as the earlycon backend for device-tree nodes whose
+/// `compatible` property matches `$compat`, so `of_setup_earlycon` can find it before the rest of
+/// the driver has probed.
+#[macro_export]
+macro_rules! of_earlycon_declare {
+    ($name:ident, $compat:literal, $ty:ty) => {
+        const _: () = {
+            const fn pad<const N: usize>(s: &str) -> [core::ffi::c_char; N] {
+                let bytes = s.as_bytes();
+                let mut out = [0 as core::ffi::c_char; N];
+                let mut i = 0;
+                while i < bytes.len() {
+                    out[i] = bytes[i] as core::ffi::c_char;
+                    i += 1;
+                }
+                out
+            }
+
+            #[used]
+            #[link_section = "__earlycon_of_table"]
+            static EARLYCON_ID: $crate::bindings::earlycon_id = $crate::bindings::earlycon_id {
+                name: pad(core::stringify!($name)),
+                compatible: pad($compat),
+                data: core::ptr::null(),
+                setup: Some(
+                    $crate::serial_core::uart_driver::EarlyConVtable::<$ty>::probe,
+                ),
+            };
+        };
+    };
 }
@@ -4,15 +4,18 @@ use kernel::error::Result;
 use kernel::prelude::InPlaceInit;
 use kernel::{prelude::*, serial_core, console};
 use kernel::serial_core::uart_port;
+use kernel::serial_core::uart_port::BufferedUart;
 use kernel::sync::Arc;
 use kernel::{
     amba, define_amba_id_table,
+    container_of,
     error::{self, Error},
     module_amba_driver, pr_info, pr_warn,
     serial_core::uart_port::UartPort,
 };
 
 const UART_NR: usize = 14;
+const BUF_SIZE: usize = 256;
 
 struct AmbaPorts {
     ports: Vec<Option<Arc<AmbaUartPort>>>,
@@ -59,6 +62,8 @@ impl AmbaPorts {
 struct AmbaUartPort {
     #[pin]
     uart_port: UartPort,
+    tx_buf: [u8; BUF_SIZE],
+    rx_buf: [u8; BUF_SIZE],
 }
 
 unsafe impl Sync for AmbaUartPort {}
@@ -67,124 +72,41 @@ unsafe impl Send for AmbaUartPort {}
 impl AmbaUartPort {
     fn try_new() -> Result<Arc<Self>> {
         Ok(Arc::pin_init(pin_init!(Self {
-            uart_port: UartPort::new::<AmbaUartOps>()
+            uart_port: UartPort::new::<AmbaUartOps>(),
+            tx_buf: [0; BUF_SIZE],
+            rx_buf: [0; BUF_SIZE],
         }))?)
     }
-}
-
-struct AmbaUartOps {}
 
-impl uart_port::UartOps for AmbaUartOps {
-    fn tx_empty(uart_port: &mut UartPort) -> u32 {
-        unimplemented!()
+    /// Recovers the owning `AmbaUartPort` from the `UartPort` embedded at its `uart_port` field.
+    fn from_uart_port(uart_port: &mut UartPort) -> &mut Self {
+        unsafe { &mut *container_of!(uart_port as *mut UartPort, Self, uart_port) }
     }
 
-    fn set_mctrl(uart_port: &mut UartPort, mctrl: u32) {
-        unimplemented!()
+    fn buffers(&mut self) -> BufferedUart<'_> {
+        BufferedUart::new(&mut self.tx_buf, &mut self.rx_buf)
     }
+}
 
-    fn get_mctrl(uart_port: &mut UartPort) -> u32 {
-        unimplemented!()
-    }
+struct AmbaUartOps {}
 
+#[vtable]
+impl uart_port::UartOps for AmbaUartOps {
     fn stop_tx(uart_port: &mut UartPort) {
-        unimplemented!()
-    }
-
-    fn start_tx(uart_port: &mut UartPort) {
-        unimplemented!()
-    }
-
-    fn throttle(uart_port: &mut UartPort) {
-        unimplemented!()
-    }
-
-    fn unthrottle(uart_port: &mut UartPort) {
-        unimplemented!()
-    }
-
-    fn send_xchar(uart_port: &mut UartPort, ch: i8) {
-        unimplemented!()
-    }
-
-    fn stop_rx(uart_port: &mut UartPort) {
-        unimplemented!()
+        // Nothing left to hand to the hardware; drop whatever is still staged in the TX ring.
+        let port = AmbaUartPort::from_uart_port(uart_port);
+        let buffers = port.buffers();
+        let pending = buffers.tx.pop_buf().len();
+        buffers.tx.pop(pending);
     }
 
     fn start_rx(uart_port: &mut UartPort) {
-        unimplemented!()
-    }
-
-    fn enable_ms(uart_port: &mut UartPort) {
-        unimplemented!()
-    }
-
-    fn break_ctl(uart_port: &mut UartPort, ctl: i32) {
-        unimplemented!()
-    }
-
-    fn startup(uart_port: &mut UartPort) -> i32 {
-        unimplemented!()
-    }
-
-    fn shutdown(uart_port: &mut UartPort) {
-        unimplemented!()
-    }
-
-    fn flush_buffer(uart_port: &mut UartPort) {
-        unimplemented!()
-    }
-
-    fn set_termios(
-        uart_port: &mut UartPort,
-        new: *mut serial_core::uart_port::ktermios,
-        old: *const serial_core::uart_port::ktermios,
-    ) {
-        unimplemented!()
-    }
-
-    fn set_ldisc(uart_port: &mut UartPort, arg2: *mut serial_core::uart_port::ktermios) {
-        unimplemented!()
-    }
-
-    fn pm(uart_port: &mut UartPort, state: u32, oldstate: u32) {
-        unimplemented!()
-    }
-
-    fn type_(uart_port: &mut UartPort) -> *const i8 {
-        unimplemented!()
-    }
-
-    fn release_port(uart_port: &mut UartPort) {
-        unimplemented!()
-    }
-
-    fn request_port(uart_port: &mut UartPort) -> i32 {
-        unimplemented!()
-    }
-
-    fn config_port(uart_port: &mut UartPort, arg2: i32) {
-        unimplemented!()
-    }
-
-    fn verify_port(uart_port: &mut UartPort, arg2: *mut serial_core::uart_port::serial_struct) -> i32 {
-        unimplemented!()
-    }
-
-    fn ioctl(uart_port: &mut UartPort, arg2: u32, arg3: u64) -> i32 {
-        unimplemented!()
-    }
-
-    fn poll_init(uart_port: &mut UartPort) -> i32 {
-        unimplemented!()
-    }
-
-    fn poll_put_char(uart_port: &mut UartPort, arg2: u8) {
-        unimplemented!()
-    }
-
-    fn poll_get_char(uart_port: &mut UartPort) -> i32 {
-        unimplemented!()
+        // Reset the RX ring so the next interrupt starts filling from a clean slate.
+        let port = AmbaUartPort::from_uart_port(uart_port);
+        let buffers = port.buffers();
+        let filled = buffers.rx.pop_buf().len();
+        buffers.rx.pop(filled);
+        let _ = buffers.rx.push_buf();
     }
 }
 